@@ -20,10 +20,10 @@ use std::path::{Path, PathBuf};
 
 
 fn list_mount(target: &Path) {
-    match get_mount(&target) {
+    match get_mount(target) {
         Ok(list) => {
             match list {
-                Some(mount) => println!("Mount point: {:?}", mount),
+                Some(mount) => println!("Mount point: {}", mount.summary()),
                 None => println!("No mount point for {}", target.display()),
             }
         },
@@ -32,7 +32,7 @@ fn list_mount(target: &Path) {
 }
 
 fn main() {
-    let target = match args().skip(1).next() {
+    let target = match args().nth(1) {
         Some(target) => PathBuf::from(target),
         None => PathBuf::from("/"),
     };
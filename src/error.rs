@@ -17,35 +17,83 @@ use std::error::Error;
 use std::fmt;
 use std::io;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ParseErrorKind {
+    Io,
+    Parse,
+}
+
 #[derive(Debug)]
 pub struct ParseError {
     desc: String,
-    // TODO: cause: Option<&'a (Error + 'a)>,
+    kind: ParseErrorKind,
+    cause: Option<Box<dyn Error + Send + Sync>>,
 }
 
 impl ParseError {
     pub fn new(detail: String) -> ParseError {
         ParseError {
             desc: format!("Mount parsing: {}", detail),
+            kind: ParseErrorKind::Parse,
+            cause: None,
         }
     }
+
+    /// Wrap a `LineError` occurring at the line number `nb`
+    pub fn at_line(nb: usize, err: LineError) -> ParseError {
+        // A single top-level prefix
+        ParseError::new(format!("Failed at line {}: {}", nb, err.detail())).caused_by(err)
+    }
+
+    fn caused_by<E>(mut self, err: E) -> ParseError where E: Error + Send + Sync + 'static {
+        self.cause = Some(Box::new(err));
+        self
+    }
+
+    /// Check if the error comes from a failure to read the mounts file
+    pub fn is_io(&self) -> bool {
+        self.kind == ParseErrorKind::Io
+    }
+
+    /// Check if the error comes from malformed data
+    pub fn is_parse(&self) -> bool {
+        self.kind == ParseErrorKind::Parse
+    }
 }
 
 impl Error for ParseError {
+    #[allow(deprecated)]
     fn description(&self) -> &str {
         self.desc.as_ref()
     }
+
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause.as_ref().map(|e| e.as_ref() as &(dyn Error + 'static))
+    }
 }
 
 impl From<io::Error> for ParseError {
     fn from(err: io::Error) -> ParseError {
-        ParseError::new(format!("Failed to read the mounts file: {}", err))
+        ParseError {
+            kind: ParseErrorKind::Io,
+            ..ParseError::new(format!("Failed to read the mounts file: {}", err))
+        }.caused_by(err)
+    }
+}
+
+impl From<LineError> for ParseError {
+    fn from(err: LineError) -> ParseError {
+        // The line error message already has its own prefix
+        ParseError {
+            desc: err.to_string(),
+            ..ParseError::new(String::new())
+        }.caused_by(err)
     }
 }
 
 impl fmt::Display for ParseError {
     fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {
-        write!(out, "{}", self.description())
+        write!(out, "{}", self.desc)
     }
 }
 
@@ -61,11 +109,16 @@ pub enum LineError {
     InvalidFreq(String),
     MissingPassno,
     InvalidPassno(String),
+    InvalidUtf8(String),
+    InvalidPair(String),
 }
 
-impl fmt::Display for LineError {
-    fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {
-        let desc: Cow<_> = match *self {
+impl Error for LineError {}
+
+impl LineError {
+    /// Get the description without the "Line parsing:" prefix
+    fn detail(&self) -> Cow<'_, str> {
+        match *self {
             LineError::MissingSpec => "Missing field #1 (spec)".into(),
             LineError::MissingFile => "Missing field #2 (file)".into(),
             LineError::InvalidFilePath(ref f) => format!("Bad field #2 (file) value (not absolute path): {}", f).into(),
@@ -76,7 +129,52 @@ impl fmt::Display for LineError {
             LineError::InvalidFreq(ref f) => format!("Bad field #5 (dump) value: {}", f).into(),
             LineError::MissingPassno => "Missing field #6 (passno)".into(),
             LineError::InvalidPassno(ref f) => format!("Bad field #6 (passno) value: {}", f).into(),
-        };
-        write!(out, "Line parsing: {}", desc)
+            LineError::InvalidUtf8(ref f) => format!("Bad field value (not UTF-8): {}", f).into(),
+            LineError::InvalidPair(ref f) => format!("Bad key=\"value\" pair: {}", f).into(),
+        }
+    }
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {
+        write!(out, "Line parsing: {}", self.detail())
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+    use std::fs::File;
+    use std::io;
+    use super::{LineError, ParseError};
+
+    #[test]
+    fn test_parse_error_kind() {
+        let err = ParseError::from(File::open("/nonexistent/mounts").unwrap_err());
+        assert!(err.is_io());
+        assert!(!err.is_parse());
+        assert!(err.to_string().starts_with("Mount parsing: Failed to read the mounts file: "));
+
+        let err = ParseError::new("Failed at line 0: bad".to_string());
+        assert!(err.is_parse());
+        assert!(!err.is_io());
+    }
+
+    #[test]
+    fn test_parse_error_source() {
+        let err = ParseError::from(LineError::MissingSpec);
+        assert!(err.is_parse());
+        assert_eq!(err.to_string(), "Line parsing: Missing field #1 (spec)");
+        assert_eq!(err.source().and_then(|e| e.downcast_ref::<LineError>()), Some(&LineError::MissingSpec));
+
+        let err = ParseError::at_line(3, LineError::InvalidFreq("2".to_string()));
+        assert_eq!(err.to_string(), "Mount parsing: Failed at line 3: Bad field #5 (dump) value: 2");
+        assert_eq!(err.source().and_then(|e| e.downcast_ref::<LineError>()),
+                   Some(&LineError::InvalidFreq("2".to_string())));
+
+        let err = ParseError::from(File::open("/nonexistent/mounts").unwrap_err());
+        assert_eq!(err.source().and_then(|e| e.downcast_ref::<io::Error>()).map(|e| e.kind()),
+                   Some(io::ErrorKind::NotFound));
     }
 }
@@ -0,0 +1,135 @@
+// Copyright (C) 2014-2015 Mickaël Salaün
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Interoperability with the `findmnt` tool output
+
+use error::LineError;
+use parse::{DumpField, MntOps, MountEntry};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Split a `findmnt --pairs` line into its `KEY="value"` pairs
+///
+/// The `\xNN` escapes are decoded.
+pub fn split_pairs(line: &str) -> Result<Vec<(&str, String)>, LineError> {
+    let mut ret = vec!();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        let eq = rest.find("=\"").ok_or_else(|| LineError::InvalidPair(rest.into()))?;
+        let key = &rest[..eq];
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(LineError::InvalidPair(rest.into()));
+        }
+        let value = &rest[eq + 2..];
+        let end = value.find('"').ok_or_else(|| LineError::InvalidPair(rest.into()))?;
+        ret.push((key, unescape(&value[..end]).ok_or_else(|| LineError::InvalidPair(rest.into()))?));
+        rest = value[end + 1..].trim_start();
+    }
+    Ok(ret)
+}
+
+fn unescape(value: &str) -> Option<String> {
+    let mut ret = vec!();
+    let bytes = value.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && bytes.get(i + 1) == Some(&b'x') {
+            let hex = value.get(i + 2..i + 4)?;
+            ret.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 4;
+        } else {
+            ret.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(ret).ok()
+}
+
+/// Parse a `findmnt --pairs` line (e.g. `findmnt --pairs -o SOURCE,TARGET,FSTYPE,OPTIONS`)
+///
+/// The `SOURCE`, `TARGET`, `FSTYPE` and `OPTIONS` keys are required whereas `FREQ` and `PASSNO`
+/// default to 0.  Other keys are ignored.
+pub fn parse_pairs(line: &str) -> Result<MountEntry, LineError> {
+    let pairs = split_pairs(line)?;
+    let get = |key: &str| pairs.iter().find(|&&(k, _)| k == key).map(|(_, v)| v.as_str());
+    Ok(MountEntry {
+        spec: get("SOURCE").ok_or(LineError::MissingSpec)?.to_string(),
+        file: {
+            let file = get("TARGET").ok_or(LineError::MissingFile)?;
+            let path = PathBuf::from(file);
+            if path.is_relative() {
+                return Err(LineError::InvalidFilePath(file.into()));
+            }
+            path
+        },
+        vfstype: get("FSTYPE").ok_or(LineError::MissingVfstype)?.to_string(),
+        mntops: MntOps::parse_list(get("OPTIONS").ok_or(LineError::MissingMntops)?),
+        freq: match get("FREQ") {
+            None | Some("0") => DumpField::Ignore,
+            Some("1") => DumpField::Backup,
+            Some(freq) => return Err(LineError::InvalidFreq(freq.into())),
+        },
+        passno: match get("PASSNO") {
+            None => None,
+            Some(passno) => match FromStr::from_str(passno) {
+                Ok(0) => None,
+                Ok(f) if f > 0 => Some(f),
+                _ => return Err(LineError::InvalidPassno(passno.into())),
+            },
+        },
+        extra_fields: vec!(),
+        raw: None,
+    })
+}
+
+
+#[cfg(test)]
+mod test {
+    use error::LineError;
+    use parse::{DumpField, MntOps, MountEntry};
+    use std::path::PathBuf;
+    use super::{parse_pairs, split_pairs};
+
+    #[test]
+    fn test_split_pairs() {
+        assert_eq!(split_pairs(r#"TARGET="/mnt/my disk" LABEL="a\x22b""#),
+                   Ok(vec!(("TARGET", "/mnt/my disk".to_string()), ("LABEL", "a\"b".to_string()))));
+        assert_eq!(split_pairs(""), Ok(vec!()));
+        assert!(split_pairs(r#"TARGET="/mnt"#).is_err());
+        assert!(split_pairs(r#"TARGET=/mnt"#).is_err());
+        assert!(split_pairs(r#"TARGET="\x2""#).is_err());
+    }
+
+    #[test]
+    fn test_parse_pairs() {
+        let line = r#"SOURCE="/dev/sda1" TARGET="/mnt/my disk" FSTYPE="ext4" OPTIONS="rw,nosuid,relatime,data=ordered" FREQ="1" PASSNO="2""#;
+        assert_eq!(parse_pairs(line), Ok(MountEntry {
+            spec: "/dev/sda1".to_string(),
+            file: PathBuf::from("/mnt/my disk"),
+            vfstype: "ext4".to_string(),
+            mntops: vec!(MntOps::Write(true), MntOps::Suid(false), MntOps::RelAtime(true),
+                         MntOps::Extra("data=ordered".to_string())),
+            freq: DumpField::Backup,
+            passno: Some(2),
+            extra_fields: vec!(),
+            raw: None,
+        }));
+        let line = r#"SOURCE="sysfs" TARGET="/sys" FSTYPE="sysfs" OPTIONS="rw""#;
+        assert_eq!(parse_pairs(line).map(|m| (m.freq, m.passno)), Ok((DumpField::Ignore, None)));
+        let line = r#"SOURCE="sysfs" FSTYPE="sysfs" OPTIONS="rw""#;
+        assert_eq!(parse_pairs(line), Err(LineError::MissingFile));
+        let line = r#"SOURCE="sysfs" TARGET="/sys" FSTYPE="sysfs" OPTIONS="rw" PASSNO="-1""#;
+        assert_eq!(parse_pairs(line), Err(LineError::InvalidPassno("-1".to_string())));
+    }
+}
@@ -0,0 +1,64 @@
+// Copyright (C) 2014-2015 Mickaël Salaün
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use parse::MountEntry;
+use std::fmt::Write;
+
+/// Quote a JSON string
+fn quote(value: &str) -> String {
+    let mut ret = String::with_capacity(value.len() + 2);
+    ret.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => ret.push_str("\\\""),
+            '\\' => ret.push_str("\\\\"),
+            '\n' => ret.push_str("\\n"),
+            '\t' => ret.push_str("\\t"),
+            c if (c as u32) < 0x20 => { let _ = write!(ret, "\\u{:04x}", c as u32); }
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+impl MountEntry {
+    /// Get a JSON object with the fields of this mount point
+    ///
+    /// The keys are always in the same order, the options are an array of tokens and a missing
+    /// pass number is `null`. A mount point which is not valid UTF-8 is lossily converted.
+    pub fn as_json(&self) -> String {
+        let mntops = self.mntops.iter().map(|o| quote(&o.to_string())).collect::<Vec<_>>().join(",");
+        format!("{{\"spec\":{},\"file\":{},\"vfstype\":{},\"mntops\":[{}],\"freq\":{},\"passno\":{}}}",
+                quote(&self.spec), quote(&self.file.to_string_lossy()), quote(&self.vfstype), mntops,
+                self.freq as i32, self.passno.map_or("null".to_string(), |p| p.to_string()))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use parse::MountEntry;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_as_json() {
+        let boot = MountEntry::from_str("/dev/sda1 /boot ext4 rw,seclabel,relatime,data=ordered 0 2").unwrap();
+        assert_eq!(boot.as_json(), "{\"spec\":\"/dev/sda1\",\"file\":\"/boot\",\"vfstype\":\"ext4\",\
+            \"mntops\":[\"rw\",\"seclabel\",\"relatime\",\"data=ordered\"],\"freq\":0,\"passno\":2}");
+        let odd = MountEntry::from_str("a\"b /mnt/c\\134d tmpfs rw 1 0").unwrap();
+        assert_eq!(odd.as_json(), "{\"spec\":\"a\\\"b\",\"file\":\"/mnt/c\\\\d\",\"vfstype\":\"tmpfs\",\
+            \"mntops\":[\"rw\"],\"freq\":1,\"passno\":null}");
+    }
+}
@@ -13,7 +13,25 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 pub use error::*;
+#[cfg(feature = "nix")]
+pub use nix::*;
 pub use parse::*;
+#[cfg(feature = "resolve")]
+pub use spec::*;
+#[cfg(all(unix, feature = "statvfs"))]
+pub use usage::*;
 
 mod error;
+pub mod findmnt;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "nix")]
+mod nix;
 mod parse;
+pub mod prelude;
+#[cfg(feature = "resolve")]
+mod spec;
+#[cfg(all(unix, feature = "statcheck"))]
+mod statcheck;
+#[cfg(all(unix, feature = "statvfs"))]
+mod usage;
@@ -0,0 +1,173 @@
+// Copyright (C) 2014-2015 Mickaël Salaün
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+
+extern crate libc;
+extern crate nix;
+
+use parse::{MntOps, MountEntry, Propagation};
+use self::nix::mount::MsFlags;
+use std::path::{Path, PathBuf};
+
+impl MountEntry {
+    /// Get the `nix::mount::mount` arguments to replicate this mount point
+    ///
+    /// Return the source, target, filesystem type, flags and data (i.e. the `Extra` options).
+    pub fn to_nix_mount_args(&self) -> (Option<&Path>, &Path, &str, MsFlags, Option<String>) {
+        let source = match self.spec.as_ref() {
+            "none" => None,
+            spec => Some(Path::new(spec)),
+        };
+        let mut flags = MsFlags::empty();
+        let mut data = vec!();
+        for op in self.mntops.iter() {
+            match *op {
+                // The last boolean option takes precedence, as for `flags()`
+                MntOps::Atime(b) => flags.set(MsFlags::MS_NOATIME, !b),
+                MntOps::DirAtime(b) => flags.set(MsFlags::MS_NODIRATIME, !b),
+                MntOps::RelAtime(b) => flags.set(MsFlags::MS_RELATIME, b),
+                MntOps::Dev(b) => flags.set(MsFlags::MS_NODEV, !b),
+                MntOps::Exec(b) => flags.set(MsFlags::MS_NOEXEC, !b),
+                MntOps::Suid(b) => flags.set(MsFlags::MS_NOSUID, !b),
+                MntOps::Write(b) => flags.set(MsFlags::MS_RDONLY, !b),
+                MntOps::Sync(b) => flags.set(MsFlags::MS_SYNCHRONOUS, b),
+                MntOps::DirSync => flags |= MsFlags::MS_DIRSYNC,
+                MntOps::Mand(b) => flags.set(MsFlags::MS_MANDLOCK, b),
+                MntOps::Silent(b) => flags.set(MsFlags::MS_SILENT, b),
+                MntOps::LazyTime(b) => flags.set(MsFlags::MS_LAZYTIME, b),
+                // Not yet defined by nix
+                MntOps::SymFollow(b) => flags.set(MsFlags::from_bits_retain(libc::MS_NOSYMFOLLOW), !b),
+                MntOps::Bind => flags |= MsFlags::MS_BIND,
+                MntOps::RBind => flags |= MsFlags::MS_BIND | MsFlags::MS_REC,
+                MntOps::Move => flags |= MsFlags::MS_MOVE,
+                MntOps::Propagation(propagation, recursive) => {
+                    flags |= match propagation {
+                        Propagation::Shared => MsFlags::MS_SHARED,
+                        Propagation::Slave => MsFlags::MS_SLAVE,
+                        Propagation::Private => MsFlags::MS_PRIVATE,
+                        Propagation::Unbindable => MsFlags::MS_UNBINDABLE,
+                    };
+                    if recursive {
+                        flags |= MsFlags::MS_REC;
+                    }
+                }
+                MntOps::Extra(ref extra) => data.push(extra.as_str()),
+                _ => {}
+            }
+        }
+        let data = if data.is_empty() {
+            None
+        } else {
+            Some(data.join(","))
+        };
+        (source, &self.file, &self.vfstype, flags, data)
+    }
+
+    /// Get everything needed to recreate this mount point (e.g. in another mount namespace)
+    ///
+    /// A bind mount is recognized by its `bind` or `rbind` option, in which case the source is the
+    /// bound path and the filesystem type is ignored by `mount(2)`.
+    pub fn clone_into_namespace_spec(&self) -> MountSpec {
+        let (source, target, fstype, flags, data) = self.to_nix_mount_args();
+        MountSpec {
+            source: source.map(Path::to_path_buf),
+            target: target.to_path_buf(),
+            fstype: fstype.to_string(),
+            flags,
+            data,
+        }
+    }
+}
+
+/// Parameters of `mount(2)` to recreate a mount point
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct MountSpec {
+    /// `None` for a `none` source
+    pub source: Option<PathBuf>,
+    pub target: PathBuf,
+    pub fstype: String,
+    pub flags: MsFlags,
+    /// Filesystem specific options
+    pub data: Option<String>,
+}
+
+
+#[cfg(test)]
+mod test {
+    use parse::MountEntry;
+    use std::path::{Path, PathBuf};
+    use std::str::FromStr;
+    use super::libc;
+    use super::nix::mount::MsFlags;
+    use super::MountSpec;
+
+    #[test]
+    fn test_nix_mount_args() {
+        let sys = MountEntry::from_str("sysfs /sys sysfs rw,seclabel,nosuid,nodev,noexec,relatime 0 0").unwrap();
+        let (source, target, fstype, flags, data) = sys.to_nix_mount_args();
+        assert_eq!(source, Some(Path::new("sysfs")));
+        assert_eq!(target, Path::new("/sys"));
+        assert_eq!(fstype, "sysfs");
+        assert!(flags.contains(MsFlags::MS_NOSUID | MsFlags::MS_NODEV | MsFlags::MS_NOEXEC));
+        assert!(!flags.contains(MsFlags::MS_RDONLY));
+        assert_eq!(data, Some("seclabel".to_string()));
+
+        let mount = MountEntry::from_str("/dev/sda1 /mnt ext4 ro,rw,noexec,exec,nosuid,atime,noatime 0 0").unwrap();
+        let (_, _, _, flags, _) = mount.to_nix_mount_args();
+        assert_eq!(flags, MsFlags::MS_NOSUID | MsFlags::MS_NOATIME);
+        assert!(mount.flags().write && mount.flags().exec);
+        assert_eq!(mount.clone_into_namespace_spec().flags, flags);
+    }
+
+    #[test]
+    fn test_clone_into_namespace_spec() {
+        let boot = MountEntry::from_str("/dev/sda1 /boot ext4 ro,nodev,data=ordered 0 2").unwrap();
+        assert_eq!(boot.clone_into_namespace_spec(), MountSpec {
+            source: Some(PathBuf::from("/dev/sda1")),
+            target: PathBuf::from("/boot"),
+            fstype: "ext4".to_string(),
+            flags: MsFlags::MS_RDONLY | MsFlags::MS_NODEV,
+            data: Some("data=ordered".to_string()),
+        });
+        let bind = MountEntry::from_str("/srv/data /mnt/data none bind 0 0").unwrap();
+        let spec = bind.clone_into_namespace_spec();
+        assert_eq!(spec.source, Some(PathBuf::from("/srv/data")));
+        assert_eq!(spec.flags, MsFlags::MS_BIND);
+        assert_eq!(spec.data, None);
+        let proc_mount = MountEntry::from_str("none /proc proc rw 0 0").unwrap();
+        assert_eq!(proc_mount.clone_into_namespace_spec().source, None);
+    }
+
+    #[test]
+    fn test_nix_mount_args_sync() {
+        let mount = MountEntry::from_str("/dev/sdb1 /mnt ext4 sync,dirsync,mand,silent 0 0").unwrap();
+        let (_, _, _, flags, data) = mount.to_nix_mount_args();
+        assert_eq!(flags, MsFlags::MS_SYNCHRONOUS | MsFlags::MS_DIRSYNC | MsFlags::MS_MANDLOCK | MsFlags::MS_SILENT);
+        assert_eq!(data, None);
+    }
+
+    #[test]
+    fn test_nix_mount_args_lazytime_symfollow() {
+        let mount = MountEntry::from_str("/dev/sdb1 /mnt ext4 lazytime,nosymfollow 0 0").unwrap();
+        let (_, _, _, flags, _) = mount.to_nix_mount_args();
+        assert_eq!(flags, MsFlags::MS_LAZYTIME | MsFlags::from_bits_retain(libc::MS_NOSYMFOLLOW));
+    }
+
+    #[test]
+    fn test_nix_mount_args_bind() {
+        let mount = MountEntry::from_str("/srv /mnt none rbind,rprivate 0 0").unwrap();
+        let (_, _, _, flags, _) = mount.to_nix_mount_args();
+        assert_eq!(flags, MsFlags::MS_BIND | MsFlags::MS_REC | MsFlags::MS_PRIVATE);
+    }
+}
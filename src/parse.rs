@@ -13,19 +13,47 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 extern crate libc;
+#[cfg(feature = "flate2")]
+extern crate flate2;
 
 use error::*;
+#[cfg(feature = "flate2")]
+use self::flate2::read::GzDecoder;
 use self::libc::c_int;
+use std::borrow::Cow;
 use std::cmp::Ordering;
-use std::convert::{AsRef, From};
+use std::collections::HashMap;
+use std::convert::{AsRef, From, TryFrom};
+#[cfg(unix)]
+use std::ffi::OsStr;
 use std::fmt;
-use std::fs::File;
-use std::io::{BufReader, BufRead, Lines};
-use std::iter::Enumerate;
+use std::fs::{self, File};
+use std::io::{BufReader, BufRead, Read};
+use std::iter::FromIterator;
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+#[cfg(unix)]
+use std::os::unix::io::{FromRawFd, RawFd};
 use std::path::{Path, PathBuf};
-use std::str::FromStr;
+use std::str::{self, FromStr};
 
-const PROC_MOUNTS: &'static str = "/proc/mounts";
+const PROC_ROOT: &str = "/proc";
+
+/// Virtual filesystem types, not backed by any storage
+pub const PSEUDO_FS_TYPES: &[&str] = &[
+    "autofs", "binfmt_misc", "bpf", "cgroup", "cgroup2", "configfs", "debugfs", "devpts",
+    "devtmpfs", "efivarfs", "fusectl", "hugetlbfs", "mqueue", "nfsd", "nsfs", "proc", "pstore",
+    "ramfs", "rootfs", "rpc_pipefs", "securityfs", "selinuxfs", "sysfs", "tmpfs", "tracefs",
+];
+
+/// Filesystem types backed by a remote server
+///
+/// The local control filesystems of the NFS server and client (`nfsd` and `rpc_pipefs`) are not
+/// part of it.
+pub const NETWORK_FS_TYPES: &[&str] = &[
+    "9p", "afs", "ceph", "cifs", "fuse.glusterfs", "fuse.s3fs", "fuse.sshfs", "glusterfs", "ncpfs",
+    "nfs", "nfs4", "smb3", "smbfs",
+];
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum DumpField {
@@ -35,6 +63,29 @@ pub enum DumpField {
 
 pub type PassField = Option<c_int>;
 
+/// Mount propagation type, as set with a mount command
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Propagation {
+    Shared,
+    Slave,
+    Private,
+    Unbindable,
+}
+
+/// Mount option
+///
+/// A boolean option is displayed with its positive token when `true` and with the `no`-prefixed
+/// token when `false`, except for the following ones:
+///
+/// | Variant  | `true`   | `false` |
+/// |----------|----------|---------|
+/// | `Write`  | `rw`     | `ro`    |
+/// | `Sync`   | `sync`   | `async` |
+/// | `Silent` | `silent` | `loud`  |
+///
+/// A recursive `Propagation` is `r`-prefixed (e.g. `rshared`) and the other variants are
+/// displayed with their lowercase name.  Parsing a displayed option gives back the same value,
+/// and unknown options are kept verbatim in `Extra`.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum MntOps {
     Atime(bool),
@@ -44,6 +95,25 @@ pub enum MntOps {
     Exec(bool),
     Suid(bool),
     Write(bool),
+    /// `sync` or `async`
+    Sync(bool),
+    DirSync,
+    /// `mand` or `nomand`
+    Mand(bool),
+    /// `silent` or `loud`
+    Silent(bool),
+    LazyTime(bool),
+    /// `symfollow` or `nosymfollow`
+    SymFollow(bool),
+    /// ID-mapped mount (read-only information)
+    IdMapped,
+    // The following options only come from mount commands (e.g. `mount -o bind,ro`)
+    Bind,
+    /// Recursive bind
+    RBind,
+    Move,
+    /// Propagation type, recursive or not (e.g. `rshared`)
+    Propagation(Propagation, bool),
     Extra(String),
 }
 
@@ -66,23 +136,503 @@ impl FromStr for MntOps {
             "nosuid" => MntOps::Suid(false),
             "rw" => MntOps::Write(true),
             "ro" => MntOps::Write(false),
+            "sync" => MntOps::Sync(true),
+            "async" => MntOps::Sync(false),
+            "dirsync" => MntOps::DirSync,
+            "mand" => MntOps::Mand(true),
+            "nomand" => MntOps::Mand(false),
+            "silent" => MntOps::Silent(true),
+            "loud" => MntOps::Silent(false),
+            "lazytime" => MntOps::LazyTime(true),
+            "nolazytime" => MntOps::LazyTime(false),
+            "symfollow" => MntOps::SymFollow(true),
+            "nosymfollow" => MntOps::SymFollow(false),
+            "idmapped" => MntOps::IdMapped,
+            "bind" => MntOps::Bind,
+            "rbind" => MntOps::RBind,
+            "move" => MntOps::Move,
+            "shared" => MntOps::Propagation(Propagation::Shared, false),
+            "rshared" => MntOps::Propagation(Propagation::Shared, true),
+            "slave" => MntOps::Propagation(Propagation::Slave, false),
+            "rslave" => MntOps::Propagation(Propagation::Slave, true),
+            "private" => MntOps::Propagation(Propagation::Private, false),
+            "rprivate" => MntOps::Propagation(Propagation::Private, true),
+            "unbindable" => MntOps::Propagation(Propagation::Unbindable, false),
+            "runbindable" => MntOps::Propagation(Propagation::Unbindable, true),
             // TODO: Replace with &str
             extra => MntOps::Extra(extra.to_string()),
         })
     }
 }
 
+impl<'a> TryFrom<&'a str> for MntOps {
+    type Error = LineError;
+
+    fn try_from(token: &'a str) -> Result<MntOps, LineError> {
+        MntOps::from_str(token)
+    }
+}
+
+impl fmt::Display for MntOps {
+    fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {
+        let token = match *self {
+            MntOps::Atime(true) => "atime",
+            MntOps::Atime(false) => "noatime",
+            MntOps::DirAtime(true) => "diratime",
+            MntOps::DirAtime(false) => "nodiratime",
+            MntOps::RelAtime(true) => "relatime",
+            MntOps::RelAtime(false) => "norelatime",
+            MntOps::Dev(true) => "dev",
+            MntOps::Dev(false) => "nodev",
+            MntOps::Exec(true) => "exec",
+            MntOps::Exec(false) => "noexec",
+            MntOps::Suid(true) => "suid",
+            MntOps::Suid(false) => "nosuid",
+            MntOps::Write(true) => "rw",
+            MntOps::Write(false) => "ro",
+            MntOps::Sync(true) => "sync",
+            MntOps::Sync(false) => "async",
+            MntOps::DirSync => "dirsync",
+            MntOps::Mand(true) => "mand",
+            MntOps::Mand(false) => "nomand",
+            MntOps::Silent(true) => "silent",
+            MntOps::Silent(false) => "loud",
+            MntOps::LazyTime(true) => "lazytime",
+            MntOps::LazyTime(false) => "nolazytime",
+            MntOps::SymFollow(true) => "symfollow",
+            MntOps::SymFollow(false) => "nosymfollow",
+            MntOps::IdMapped => "idmapped",
+            MntOps::Bind => "bind",
+            MntOps::RBind => "rbind",
+            MntOps::Move => "move",
+            MntOps::Propagation(Propagation::Shared, false) => "shared",
+            MntOps::Propagation(Propagation::Shared, true) => "rshared",
+            MntOps::Propagation(Propagation::Slave, false) => "slave",
+            MntOps::Propagation(Propagation::Slave, true) => "rslave",
+            MntOps::Propagation(Propagation::Private, false) => "private",
+            MntOps::Propagation(Propagation::Private, true) => "rprivate",
+            MntOps::Propagation(Propagation::Unbindable, false) => "unbindable",
+            MntOps::Propagation(Propagation::Unbindable, true) => "runbindable",
+            MntOps::Extra(ref extra) => extra,
+        };
+        write!(out, "{}", token)
+    }
+}
+
+/// Group of options setting the same flag, whatever its value (e.g. `atime` and `noatime`)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OpFamily {
+    Atime,
+    DirAtime,
+    RelAtime,
+    Dev,
+    Exec,
+    Suid,
+    Write,
+    Sync,
+    Mand,
+    Silent,
+    LazyTime,
+    SymFollow,
+    Propagation,
+}
+
+impl MntOps {
+    /// Get the family of a boolean option, if any
+    pub fn family(&self) -> Option<OpFamily> {
+        Some(match *self {
+            MntOps::Atime(_) => OpFamily::Atime,
+            MntOps::DirAtime(_) => OpFamily::DirAtime,
+            MntOps::RelAtime(_) => OpFamily::RelAtime,
+            MntOps::Dev(_) => OpFamily::Dev,
+            MntOps::Exec(_) => OpFamily::Exec,
+            MntOps::Suid(_) => OpFamily::Suid,
+            MntOps::Write(_) => OpFamily::Write,
+            MntOps::Sync(_) => OpFamily::Sync,
+            MntOps::Mand(_) => OpFamily::Mand,
+            MntOps::Silent(_) => OpFamily::Silent,
+            MntOps::LazyTime(_) => OpFamily::LazyTime,
+            MntOps::SymFollow(_) => OpFamily::SymFollow,
+            MntOps::Propagation(..) => OpFamily::Propagation,
+            _ => return None,
+        })
+    }
+
+    /// Parse a comma-separated list of options (e.g. `rw,nosuid,size=755`)
+    ///
+    /// Empty options are skipped and commas in double quotes (e.g. SELinux contexts) don't split.
+    pub fn parse_list(ops: &str) -> Vec<MntOps> {
+        // Unknown options are `Extra`, so there is no error
+        ops.split(split_option()).filter(|x| { !x.is_empty() }).filter_map(|x| { FromStr::from_str(x).ok() }).collect()
+    }
+
+    /// Check if this option restricts what the filesystem can be used for (`suid`, `dev`, `exec`
+    /// and `rw` and their negations)
+    pub fn is_security_relevant(&self) -> bool {
+        matches!(*self, MntOps::Suid(_) | MntOps::Dev(_) | MntOps::Exec(_) | MntOps::Write(_))
+    }
+}
+
+/// Restrictions set by the security relevant options, cf. `MntOps::is_security_relevant`
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct SecurityFlags {
+    pub nosuid: bool,
+    pub nodev: bool,
+    pub noexec: bool,
+    pub readonly: bool,
+}
+
+/// Boolean view of the mount options
+///
+/// An unset option takes its default value: `true` for all but `relatime`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MountFlags {
+    pub write: bool,
+    pub exec: bool,
+    pub suid: bool,
+    pub dev: bool,
+    pub atime: bool,
+    pub relatime: bool,
+    pub diratime: bool,
+}
+
+impl Default for MountFlags {
+    fn default() -> MountFlags {
+        MountFlags {
+            write: true,
+            exec: true,
+            suid: true,
+            dev: true,
+            atime: true,
+            relatime: false,
+            diratime: true,
+        }
+    }
+}
+
+/// Compare two option lists regardless of their order
+pub fn mntops_eq(a: &[MntOps], b: &[MntOps]) -> bool {
+    let count = |ops: &[MntOps], op: &MntOps| ops.iter().filter(|x| *x == op).count();
+    a.len() == b.len() && a.iter().all(|op| count(a, op) == count(b, op))
+}
+
+/// Add the implicit default of each flag not already set
+///
+/// | Family     | Default    |
+/// |------------|------------|
+/// | `Write`    | `rw`       |
+/// | `Suid`     | `suid`     |
+/// | `Dev`      | `dev`      |
+/// | `Exec`     | `exec`     |
+/// | `Atime`    | `atime`, unless `relatime` is set |
+/// | `DirAtime` | `diratime` |
+///
+/// This matches the `MountFlags` defaults.  The added options are appended in this order.
+pub fn fill_defaults(mntops: &mut Vec<MntOps>) {
+    let defaults = [
+        MntOps::Write(true),
+        MntOps::Suid(true),
+        MntOps::Dev(true),
+        MntOps::Exec(true),
+        MntOps::Atime(true),
+        MntOps::DirAtime(true),
+    ];
+    let relatime = mntops.contains(&MntOps::RelAtime(true));
+    for default in defaults.iter() {
+        if *default == MntOps::Atime(true) && relatime {
+            continue;
+        }
+        if !mntops.iter().any(|o| o.family() == default.family()) {
+            mntops.push(default.clone());
+        }
+    }
+}
+
+/// Sort options in a deterministic order for stable output (e.g. golden files)
+///
+/// Flags come first (`rw`/`ro`, `suid`, `dev`, `exec`, then the atime family and the others),
+/// followed by the mount command options and the sorted `Extra` ones. The sort is stable, so
+/// conflicting flags keep their relative order and the last one still takes precedence.
+pub fn canonical_order(mntops: &mut [MntOps]) {
+    fn rank(op: &MntOps) -> (u8, &str) {
+        match *op {
+            MntOps::Write(_) => (0, ""),
+            MntOps::Suid(_) => (1, ""),
+            MntOps::Dev(_) => (2, ""),
+            MntOps::Exec(_) => (3, ""),
+            MntOps::Atime(_) => (4, ""),
+            MntOps::DirAtime(_) => (5, ""),
+            MntOps::RelAtime(_) => (6, ""),
+            MntOps::LazyTime(_) => (7, ""),
+            MntOps::Sync(_) => (8, ""),
+            MntOps::DirSync => (9, ""),
+            MntOps::Mand(_) => (10, ""),
+            MntOps::Silent(_) => (11, ""),
+            MntOps::SymFollow(_) => (12, ""),
+            MntOps::IdMapped => (13, ""),
+            MntOps::Bind => (14, ""),
+            MntOps::RBind => (15, ""),
+            MntOps::Move => (16, ""),
+            MntOps::Propagation(..) => (17, ""),
+            MntOps::Extra(ref extra) => (18, extra),
+        }
+    }
+    mntops.sort_by(|a, b| rank(a).cmp(&rank(b)));
+}
+
 #[derive(Clone, Debug)]
 pub enum MountParam<'a> {
     Spec(&'a str),
     File(&'a Path),
     VfsType(&'a str),
+    /// Same as `VfsType` but ignoring the ASCII case
+    VfsTypeIgnoreCase(&'a str),
     MntOps(&'a MntOps),
+    /// Any option of this family, whatever its value
+    MntOpsFamily(OpFamily),
     Freq(&'a DumpField),
     PassNo(&'a PassField),
+    /// Same device node as the source, once both paths are canonicalized (e.g. through a by-id
+    /// symlink), or the same path if they can't be
+    DeviceNode(&'a Path),
+}
+
+/// Tuning of `MountEntry::matches_with`, strict by default
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct MatchOptions {
+    /// Compare `VfsType` ignoring the ASCII case
+    pub case_insensitive_vfstype: bool,
+    /// Compare `File` once both paths are canonicalized, if they can be
+    pub canonicalize_paths: bool,
+    /// Match `File` if the mount point is the searched path or one of its parents
+    pub prefix_mountpoint: bool,
+}
+
+/// Size limit of a memory-backed filesystem (e.g. `size=` option of tmpfs)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SizeSpec {
+    Bytes(u64),
+    /// Percentage of the physical RAM
+    Percent(u64),
+}
+
+/// Parse a size option value with an optional binary suffix (`k`, `m`, `g`, `t`, `p`, `e`) or `%`
+pub fn parse_size_option(value: &str) -> Option<SizeSpec> {
+    if let Some(percent) = value.strip_suffix('%') {
+        return percent.parse().ok().map(SizeSpec::Percent);
+    }
+    let (number, shift) = match value.char_indices().last() {
+        Some((i, suffix)) => match suffix.to_ascii_lowercase() {
+            'k' => (&value[..i], 10),
+            'm' => (&value[..i], 20),
+            'g' => (&value[..i], 30),
+            't' => (&value[..i], 40),
+            'p' => (&value[..i], 50),
+            'e' => (&value[..i], 60),
+            _ => (value, 0),
+        },
+        None => return None,
+    };
+    let number: u64 = number.parse().ok()?;
+    number.checked_mul(1 << shift).map(SizeSpec::Bytes)
+}
+
+/// Common Linux filesystem types
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum VfsType {
+    Btrfs,
+    Cgroup,
+    Cgroup2,
+    Devpts,
+    Devtmpfs,
+    Ext2,
+    Ext3,
+    Ext4,
+    /// `fuse`, `fuseblk` and their subtypes (e.g. `fuse.sshfs`)
+    Fuse,
+    Nfs,
+    Nfs4,
+    Overlay,
+    Proc,
+    Sysfs,
+    Tmpfs,
+    Vfat,
+    Xfs,
+    Other(String),
 }
 
-#[derive(Clone, PartialEq, Eq)]
+impl<'a> From<&'a str> for VfsType {
+    fn from(vfstype: &'a str) -> VfsType {
+        match vfstype {
+            "btrfs" => VfsType::Btrfs,
+            "cgroup" => VfsType::Cgroup,
+            "cgroup2" => VfsType::Cgroup2,
+            "devpts" => VfsType::Devpts,
+            "devtmpfs" => VfsType::Devtmpfs,
+            "ext2" => VfsType::Ext2,
+            "ext3" => VfsType::Ext3,
+            "ext4" => VfsType::Ext4,
+            "fuse" | "fuseblk" => VfsType::Fuse,
+            fuse if fuse.starts_with("fuse.") || fuse.starts_with("fuseblk.") => VfsType::Fuse,
+            "nfs" => VfsType::Nfs,
+            "nfs4" => VfsType::Nfs4,
+            "overlay" => VfsType::Overlay,
+            "proc" => VfsType::Proc,
+            "sysfs" => VfsType::Sysfs,
+            "tmpfs" => VfsType::Tmpfs,
+            "vfat" => VfsType::Vfat,
+            "xfs" => VfsType::Xfs,
+            other => VfsType::Other(other.to_string()),
+        }
+    }
+}
+
+/// Layers of an overlay filesystem
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct OverlayDirs {
+    pub lower: Vec<PathBuf>,
+    /// No upper directory for a read-only overlay
+    pub upper: Option<PathBuf>,
+    pub work: Option<PathBuf>,
+}
+
+/// Kind of an autofs mount point
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AutofsMode {
+    Direct,
+    Indirect,
+    Offset,
+}
+
+/// Options of an autofs mount point, set by the automount daemon
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct AutofsInfo {
+    /// File descriptor of the pipe to the daemon
+    pub fd: Option<i32>,
+    /// Process group of the daemon
+    pub pgrp: Option<i32>,
+    /// Expiration timeout in seconds (0 to never expire)
+    pub timeout: Option<u64>,
+    pub mode: Option<AutofsMode>,
+}
+
+/// Decode the octal escapes (e.g. `\054` for a comma) used by the kernel in the mounts file
+pub(crate) fn unescape_octal(value: &[u8]) -> Vec<u8> {
+    let mut ret = Vec::with_capacity(value.len());
+    let mut i = 0;
+    while i < value.len() {
+        let octal = value.get(i + 1..i + 4)
+            .filter(|o| value[i] == b'\\' && o.iter().all(|c| (b'0'..=b'7').contains(c)))
+            .and_then(|o| u8::from_str_radix(str::from_utf8(o).ok()?, 8).ok());
+        match octal {
+            Some(c) => {
+                ret.push(c);
+                i += 4;
+            }
+            None => {
+                ret.push(value[i]);
+                i += 1;
+            }
+        }
+    }
+    ret
+}
+
+/// Encode the characters the kernel escapes in the mounts file (space, tab, newline and backslash)
+///
+/// Bytes which are not valid UTF-8 are escaped too, so that they are decoded back by the parser.
+fn escape_octal(value: &[u8]) -> String {
+    let mut ret = String::with_capacity(value.len());
+    for chunk in value.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                ' ' | '\t' | '\n' | '\\' => ret.push_str(&format!("\\{:03o}", c as u8)),
+                c => ret.push(c),
+            }
+        }
+        for c in chunk.invalid() {
+            ret.push_str(&format!("\\{:03o}", c));
+        }
+    }
+    ret
+}
+
+/// Convert raw bytes to a path
+///
+/// Paths are bytes on Unix, whereas they are lossily converted from UTF-8 elsewhere.
+#[cfg(unix)]
+pub(crate) fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Get the raw bytes of a path, cf. `path_from_bytes()`
+#[cfg(unix)]
+fn path_as_bytes(path: &Path) -> Cow<'_, [u8]> {
+    Cow::Borrowed(path.as_os_str().as_bytes())
+}
+
+#[cfg(not(unix))]
+fn path_as_bytes(path: &Path) -> Cow<'_, [u8]> {
+    match path.to_string_lossy() {
+        Cow::Borrowed(path) => Cow::Borrowed(path.as_bytes()),
+        Cow::Owned(path) => Cow::Owned(path.into_bytes()),
+    }
+}
+
+/// Get a predicate splitting options on commas, except in double quotes (e.g. SELinux contexts)
+fn split_option() -> impl FnMut(char) -> bool {
+    let mut quoted = false;
+    move |c| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        c == ',' && !quoted
+    }
+}
+
+/// Split the overlay lower directories on unescaped colons
+fn split_lowerdir(value: &[u8]) -> Vec<PathBuf> {
+    let mut ret = vec!();
+    let mut dir = vec!();
+    let mut escaped = false;
+    for &c in value {
+        match c {
+            b'\\' if !escaped => escaped = true,
+            b':' if !escaped => ret.push(path_from_bytes(&dir.split_off(0))),
+            c => {
+                escaped = false;
+                dir.push(c);
+            }
+        }
+    }
+    ret.push(path_from_bytes(&dir));
+    ret
+}
+
+/// Group of related filesystem types
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FsFamily<'a> {
+    /// `ext2`, `ext3` and `ext4`
+    Ext,
+    /// `vfat`, `msdos`, `fat` and `exfat`
+    Fat,
+    /// `cgroup` and `cgroup2`
+    Cgroup,
+    /// `fuse`, `fuseblk`, `fuse.*` and `fuseblk.*` (e.g. `fuse.sshfs`)
+    Fuse,
+    /// `nfs` and `nfs4`
+    Nfs,
+    /// `tmpfs` and `ramfs`
+    Tmp,
+    Other(&'a str),
+}
+
+#[derive(Clone, Eq)]
 pub struct MountEntry {
     pub spec: String,
     pub file: PathBuf,
@@ -90,44 +640,394 @@ pub struct MountEntry {
     pub mntops: Vec<MntOps>,
     pub freq: DumpField,
     pub passno: PassField,
+    /// Trailing fields unknown to this parser (e.g. appended by a future kernel), empty for now
+    pub extra_fields: Vec<String>,
+    /// Trimmed source line, only kept if requested (cf. `MountIter::keep_raw`)
+    pub raw: Option<String>,
 }
 
 impl MountEntry {
     pub fn contains(&self, search: &MountParam) -> bool {
-        match search {
-            &MountParam::Spec(spec) => spec == &self.spec,
-            &MountParam::File(file) => file == &self.file,
-            &MountParam::VfsType(vfstype) => vfstype == &self.vfstype,
-            &MountParam::MntOps(mntops) => self.mntops.contains(mntops),
-            &MountParam::Freq(dumpfield) => dumpfield == &self.freq,
-            &MountParam::PassNo(passno) => passno == &self.passno,
+        self.matches_with(search, &MatchOptions::default())
+    }
+
+    /// Same as `contains` but with a tunable matching
+    pub fn matches_with(&self, search: &MountParam, options: &MatchOptions) -> bool {
+        let resolve = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        match *search {
+            MountParam::Spec(spec) => spec == self.spec,
+            MountParam::File(file) => {
+                let compare = |file: &Path, own: &Path| if options.prefix_mountpoint {
+                    file.starts_with(own)
+                } else {
+                    file == own
+                };
+                if options.canonicalize_paths {
+                    compare(&resolve(file), &resolve(&self.file))
+                } else {
+                    compare(file, &self.file)
+                }
+            }
+            MountParam::VfsType(vfstype) if options.case_insensitive_vfstype => vfstype.eq_ignore_ascii_case(&self.vfstype),
+            MountParam::VfsType(vfstype) => vfstype == self.vfstype,
+            MountParam::VfsTypeIgnoreCase(vfstype) => vfstype.eq_ignore_ascii_case(&self.vfstype),
+            MountParam::MntOps(mntops) => self.mntops.contains(mntops),
+            MountParam::MntOpsFamily(family) => self.mntops.iter().any(|o| o.family() == Some(family)),
+            MountParam::Freq(dumpfield) => dumpfield == &self.freq,
+            MountParam::PassNo(passno) => passno == &self.passno,
+            MountParam::DeviceNode(node) => {
+                self.source_device_path().is_some_and(|spec| resolve(spec) == resolve(node))
+            }
         }
     }
-}
 
-impl FromStr for MountEntry {
-    type Err = LineError;
+    /// Check if this mount point is `root` or beneath it
+    pub fn is_submount_of<T>(&self, root: T) -> bool where T: AsRef<Path> {
+        self.file.starts_with(root)
+    }
 
-    fn from_str(line: &str) -> Result<MountEntry, LineError> {
-        let line = line.trim();
-        let mut tokens = line.split_terminator(|s: char| { s == ' ' || s == '\t' })
-            .filter(|s| { s != &""  } );
+    /// Check if `path` is this mount point or beneath it
+    pub fn contains_path<T>(&self, path: T) -> bool where T: AsRef<Path> {
+        path.as_ref().starts_with(&self.file)
+    }
+
+    /// Check if `other` is backed by the same source device (e.g. a bind mount of it)
+    ///
+    /// Pseudo sources (e.g. `tmpfs`) never match because each mount is a distinct filesystem.
+    /// The bound subdirectory of the source is not known from the mounts file.
+    pub fn shares_source_with(&self, other: &MountEntry) -> bool {
+        self.source_device_path().is_some() && self.spec == other.spec && self.vfstype == other.vfstype
+    }
+
+    /// Get the decoded mount point, for humans
+    ///
+    /// Unlike the `Display` output, this is not escaped and can't be fed back to the parser.
+    pub fn human_path(&self) -> &Path {
+        &self.file
+    }
+
+    /// Get a key identifying the same logical mount point across snapshots
+    ///
+    /// Unlike the options, the filesystem type, mount point and source don't change on remount.
+    pub fn stable_key(&self) -> (String, PathBuf, String) {
+        (self.vfstype.clone(), self.file.clone(), self.spec.clone())
+    }
+
+    /// Get the options added and removed in `other` (e.g. the same mount point after a remount)
+    pub fn option_delta(&self, other: &MountEntry) -> (Vec<MntOps>, Vec<MntOps>) {
+        let added = other.mntops.iter().filter(|o| !self.mntops.contains(o)).cloned().collect();
+        let removed = self.mntops.iter().filter(|o| !other.mntops.contains(o)).cloned().collect();
+        (added, removed)
+    }
+
+    /// Check if all the `params` match
+    pub fn contains_all(&self, params: &[MountParam]) -> bool {
+        params.iter().all(|p| self.contains(p))
+    }
+
+    /// Check if at least one of the `params` matches
+    pub fn contains_any(&self, params: &[MountParam]) -> bool {
+        params.iter().any(|p| self.contains(p))
+    }
+
+    /// Get the options as a comma-separated string, as expected by `mount -o`
+    pub fn mount_options_string(&self) -> String {
+        self.mntops.iter().map(|o| o.to_string()).collect::<Vec<_>>().join(",")
+    }
+
+    /// Get the group of related filesystem types this mount point belongs to
+    pub fn fstype_family(&self) -> FsFamily<'_> {
+        match self.vfstype.as_ref() {
+            "ext2" | "ext3" | "ext4" => FsFamily::Ext,
+            "vfat" | "msdos" | "fat" | "exfat" => FsFamily::Fat,
+            "cgroup" | "cgroup2" => FsFamily::Cgroup,
+            "fuse" | "fuseblk" => FsFamily::Fuse,
+            fuse if fuse.starts_with("fuse.") || fuse.starts_with("fuseblk.") => FsFamily::Fuse,
+            "nfs" | "nfs4" => FsFamily::Nfs,
+            "tmpfs" | "ramfs" => FsFamily::Tmp,
+            other => FsFamily::Other(other),
+        }
+    }
+
+    /// Get the filesystem type as a `VfsType`
+    pub fn vfstype_enum(&self) -> VfsType {
+        VfsType::from(self.vfstype.as_str())
+    }
+
+    /// Check if this is an ID-mapped mount
+    pub fn is_idmapped(&self) -> bool {
+        self.mntops.contains(&MntOps::IdMapped)
+    }
+
+    /// Check if this is a FUSE (user-space) filesystem
+    pub fn is_fuse(&self) -> bool {
+        self.fstype_family() == FsFamily::Fuse
+    }
+
+    /// Get the FUSE subtype (e.g. `sshfs` for `fuse.sshfs`), if any
+    pub fn fuse_subtype(&self) -> Option<&str> {
+        let mut parts = self.vfstype.splitn(2, '.');
+        match (parts.next(), parts.next()) {
+            (Some("fuse"), Some(subtype)) | (Some("fuseblk"), Some(subtype)) => Some(subtype),
+            _ => None,
+        }
+    }
+
+    /// Get the size limit in bytes of a tmpfs-like mount point, if any
+    ///
+    /// Return `None` if there is no `size=` option or if it is a percentage of the RAM.
+    pub fn tmpfs_size_bytes(&self) -> Option<u64> {
+        // The last size option takes precedence
+        let size = self.mntops.iter().rev().find_map(|o| match *o {
+            MntOps::Extra(ref extra) if extra.starts_with("size=") => Some(&extra[5..]),
+            _ => None,
+        })?;
+        match parse_size_option(size)? {
+            SizeSpec::Bytes(bytes) => Some(bytes),
+            SizeSpec::Percent(_) => None,
+        }
+    }
+
+    /// Get a human one-liner summary, e.g. `ext4 /dev/sda1 on /boot (rw,relatime)`
+    pub fn summary(&self) -> String {
+        format!("{} {} on {} ({})", self.vfstype, self.spec, self.file.display(), self.mount_options_string())
+    }
+
+    /// Check if this filesystem is backed by a remote server (e.g. NFS or CIFS)
+    ///
+    /// The filesystem type is looked up in `NETWORK_FS_TYPES`.
+    pub fn is_network_fs(&self) -> bool {
+        NETWORK_FS_TYPES.contains(&self.vfstype.as_str())
+    }
+
+    /// Get a `findmnt` tree line for this mount point, after the `prefix` drawing the tree
+    ///
+    /// Unlike the `Display` output, this is meant for humans and can't be fed back to the parser.
+    pub fn display_findmnt_tree(&self, prefix: &str) -> String {
+        format!("{}{}  {}  {}  {}", prefix, self.file.display(), self.spec, self.vfstype, self.mount_options_string())
+    }
+
+    /// Check if this is a virtual filesystem
+    ///
+    /// The filesystem type is looked up in `PSEUDO_FS_TYPES`, otherwise the source is assumed to
+    /// be virtual if it is `none` or the filesystem type name.
+    pub fn is_pseudo_fs(&self) -> bool {
+        PSEUDO_FS_TYPES.contains(&self.vfstype.as_str()) || self.spec == "none" || self.spec == self.vfstype
+    }
+
+    /// Get the security restrictions, the last option taking precedence
+    pub fn security_flags(&self) -> SecurityFlags {
+        let flags = self.flags();
+        SecurityFlags {
+            nosuid: !flags.suid,
+            nodev: !flags.dev,
+            noexec: !flags.exec,
+            readonly: !flags.write,
+        }
+    }
+
+    /// Get the boolean options, the last one taking precedence
+    pub fn flags(&self) -> MountFlags {
+        let mut flags = MountFlags::default();
+        for op in self.mntops.iter() {
+            match *op {
+                MntOps::Atime(b) => flags.atime = b,
+                MntOps::DirAtime(b) => flags.diratime = b,
+                MntOps::RelAtime(b) => flags.relatime = b,
+                MntOps::Dev(b) => flags.dev = b,
+                MntOps::Exec(b) => flags.exec = b,
+                MntOps::Suid(b) => flags.suid = b,
+                MntOps::Write(b) => flags.write = b,
+                _ => {}
+            }
+        }
+        flags
+    }
+
+    /// Get the layers of an overlay mount point, if any
+    pub fn overlay_dirs(&self) -> Option<OverlayDirs> {
+        if self.vfstype != "overlay" {
+            return None;
+        }
+        let mut dirs = OverlayDirs {
+            lower: vec!(),
+            upper: None,
+            work: None,
+        };
+        for op in self.mntops.iter() {
+            if let MntOps::Extra(ref extra) = *op {
+                let mut kv = extra.splitn(2, '=');
+                let (key, value) = match (kv.next(), kv.next()) {
+                    (Some(key), Some(value)) => (key, unescape_octal(value.as_bytes())),
+                    _ => continue,
+                };
+                match key {
+                    "lowerdir" => dirs.lower = split_lowerdir(&value),
+                    "lowerdir+" => dirs.lower.push(path_from_bytes(&value)),
+                    "upperdir" => dirs.upper = Some(path_from_bytes(&value)),
+                    "workdir" => dirs.work = Some(path_from_bytes(&value)),
+                    _ => {}
+                }
+            }
+        }
+        Some(dirs)
+    }
+
+    /// Check if the filesystem supports security labels (`seclabel` option)
+    pub fn has_seclabel(&self) -> bool {
+        self.mntops.contains(&MntOps::Extra("seclabel".to_string()))
+    }
+
+    /// Get the SELinux context set with the `context`, `fscontext` or `defcontext` option
+    ///
+    /// The surrounding double quotes are removed.
+    pub fn selinux_context(&self) -> Option<&str> {
+        ["context", "fscontext", "defcontext"].iter().filter_map(|key| {
+            self.mntops.iter().filter_map(|op| match *op {
+                MntOps::Extra(ref extra) => extra.strip_prefix(key)?.strip_prefix('='),
+                _ => None,
+            }).next()
+        }).next().map(|value| value.trim_matches('"'))
+    }
+
+    /// Check if this mount point could be remounted read-only without breaking its children
+    ///
+    /// This is not the case for the root, pseudo filesystems, already read-only mount points, or
+    /// if any mount point beneath it in `all` is writable.  The mount point must be part of `all`.
+    pub fn can_remount_ro(&self, all: &[MountEntry]) -> bool {
+        if self.file == Path::new("/") || self.is_pseudo_fs() || !self.flags().write {
+            return false;
+        }
+        let me = match all.iter().rposition(|m| m == self) {
+            Some(me) => me,
+            None => return false,
+        };
+        let parents = tree_parents(all);
+        let is_descendant = |mut i: usize| {
+            while let Some(p) = parents[i] {
+                if p == me {
+                    return true;
+                }
+                i = p;
+            }
+            false
+        };
+        !all.iter().enumerate().any(|(i, m)| is_descendant(i) && m.flags().write)
+    }
+
+    /// Check if this is the initramfs, which stays beneath the real root filesystem
+    pub fn is_initramfs_rootfs(&self) -> bool {
+        self.vfstype == "rootfs"
+    }
+
+    /// Check if this is an autofs trigger, which may not be mounted yet
+    pub fn is_autofs(&self) -> bool {
+        self.vfstype == "autofs"
+    }
+
+    /// Get the autofs options of this mount point, if it is an autofs trigger
+    pub fn autofs_info(&self) -> Option<AutofsInfo> {
+        if !self.is_autofs() {
+            return None;
+        }
+        let mut info = AutofsInfo::default();
+        for op in self.mntops.iter() {
+            if let MntOps::Extra(ref extra) = *op {
+                let mut kv = extra.splitn(2, '=');
+                match (kv.next(), kv.next()) {
+                    (Some("fd"), Some(value)) => info.fd = value.parse().ok(),
+                    (Some("pgrp"), Some(value)) => info.pgrp = value.parse().ok(),
+                    (Some("timeout"), Some(value)) => info.timeout = value.parse().ok(),
+                    (Some("direct"), None) => info.mode = Some(AutofsMode::Direct),
+                    (Some("indirect"), None) => info.mode = Some(AutofsMode::Indirect),
+                    (Some("offset"), None) => info.mode = Some(AutofsMode::Offset),
+                    _ => {}
+                }
+            }
+        }
+        Some(info)
+    }
+
+    /// Get the source device path, if any
+    ///
+    /// Pseudo sources (e.g. `sysfs` or `tmpfs`) are not absolute paths and then return `None`.
+    pub fn source_device_path(&self) -> Option<&Path> {
+        if self.spec.starts_with('/') {
+            Some(Path::new(&self.spec))
+        } else {
+            None
+        }
+    }
+}
+
+impl MountEntry {
+    /// Parse a line from raw bytes
+    ///
+    /// On Unix, the mount point is kept as is, without requiring it to be valid UTF-8, whereas the
+    /// other fields must be valid UTF-8.
+    pub fn from_bytes(line: &[u8]) -> Result<MountEntry, LineError> {
+        let fields = LineFields::new(line)?;
         Ok(MountEntry {
-            spec: try!(tokens.next().ok_or(LineError::MissingSpec)).to_string(),
+            spec: fields.spec.into_owned(),
+            file: path_from_bytes(&fields.file),
+            vfstype: fields.vfstype.to_string(),
+            // A field without any option (e.g. ",") gives an empty list
+            mntops: MntOps::parse_list(fields.mntops),
+            freq: fields.freq,
+            passno: fields.passno,
+            extra_fields: fields.extra_fields.into_iter().map(str::to_string).collect(),
+            raw: None,
+        })
+    }
+}
+
+/// Checked fields of a line, borrowed from it as much as possible
+struct LineFields<'a> {
+    spec: Cow<'a, str>,
+    file: Cow<'a, [u8]>,
+    vfstype: &'a str,
+    mntops: &'a str,
+    freq: DumpField,
+    passno: PassField,
+    extra_fields: Vec<&'a str>,
+}
+
+impl<'a> LineFields<'a> {
+    fn new(line: &'a [u8]) -> Result<LineFields<'a>, LineError> {
+        fn utf8(token: &[u8]) -> Result<&str, LineError> {
+            str::from_utf8(token).map_err(|_| LineError::InvalidUtf8(String::from_utf8_lossy(token).into_owned()))
+        }
+        // Only allocate if there is something to decode
+        fn unescape(token: &[u8]) -> Cow<'_, [u8]> {
+            if token.contains(&b'\\') {
+                Cow::Owned(unescape_octal(token))
+            } else {
+                Cow::Borrowed(token)
+            }
+        }
+
+        let mut tokens = line.split(|s| { *s == b' ' || *s == b'\t' })
+            .map(|s| { s.trim_ascii() })
+            .filter(|s| { !s.is_empty() } );
+        Ok(LineFields {
+            spec: match unescape(tokens.next().ok_or(LineError::MissingSpec)?) {
+                Cow::Borrowed(spec) => Cow::Borrowed(utf8(spec)?),
+                Cow::Owned(spec) => Cow::Owned(utf8(&spec)?.to_string()),
+            },
             file: {
-                let file = try!(tokens.next().ok_or(LineError::MissingFile));
-                let path = PathBuf::from(file);
-                if path.is_relative() {
-                    return Err(LineError::InvalidFilePath(file.into()));
+                let file = unescape(tokens.next().ok_or(LineError::MissingFile)?);
+                if file.first() != Some(&b'/') {
+                    return Err(LineError::InvalidFilePath(String::from_utf8_lossy(&file).into_owned()));
                 }
-                path
+                // Paths are only bytes on Unix
+                #[cfg(not(unix))]
+                utf8(&file)?;
+                file
             },
-            vfstype: try!(tokens.next().ok_or(LineError::MissingVfstype)).to_string(),
-            mntops: try!(tokens.next().ok_or(LineError::MissingMntops))
-                // FIXME: Handle MntOps errors
-                .split_terminator(',').map(|x| { FromStr::from_str(x).unwrap() }).collect(),
+            vfstype: utf8(tokens.next().ok_or(LineError::MissingVfstype)?)?,
+            mntops: utf8(tokens.next().ok_or(LineError::MissingMntops)?)?,
             freq: {
-                let freq = try!(tokens.next().ok_or(LineError::MissingFreq));
+                let freq = utf8(tokens.next().ok_or(LineError::MissingFreq)?)?;
                 match FromStr::from_str(freq) {
                     Ok(0) => DumpField::Ignore,
                     Ok(1) => DumpField::Backup,
@@ -135,25 +1035,42 @@ impl FromStr for MountEntry {
                 }
             },
             passno: {
-                let passno = try!(tokens.next().ok_or(LineError::MissingPassno));
+                let passno = utf8(tokens.next().ok_or(LineError::MissingPassno)?)?;
                 match FromStr::from_str(passno) {
                     Ok(0) => None,
                     Ok(f) if f > 0 => Some(f),
                     _ => return Err(LineError::InvalidPassno(passno.into())),
                 }
             },
+            extra_fields: tokens.map(utf8).collect::<Result<_, _>>()?,
         })
     }
 }
 
+impl FromStr for MountEntry {
+    type Err = LineError;
 
-/// Get a list of all mount points from `root` and beneath using a custom `BufRead`
-pub fn get_submounts_from<T, U>(root: T, iter: MountIter<U>)
-        -> Result<Vec<MountEntry>, ParseError> where T: AsRef<Path>, U: BufRead {
+    fn from_str(line: &str) -> Result<MountEntry, LineError> {
+        MountEntry::from_bytes(line.as_bytes())
+    }
+}
+
+impl<'a> TryFrom<&'a str> for MountEntry {
+    type Error = LineError;
+
+    fn try_from(line: &'a str) -> Result<MountEntry, LineError> {
+        MountEntry::from_str(line)
+    }
+}
+
+
+/// Get a list of all mount points matching `pred` using a custom `BufRead`
+pub fn collect_matching<U, F>(iter: MountIter<U>, pred: F)
+        -> Result<Vec<MountEntry>, ParseError> where U: BufRead, F: Fn(&MountEntry) -> bool {
     let mut ret = vec!();
     for mount in iter {
         match mount {
-            Ok(m) => if m.file.starts_with(&root) {
+            Ok(m) => if pred(&m) {
                 ret.push(m);
             },
             Err(e) => return Err(e),
@@ -162,9 +1079,39 @@ pub fn get_submounts_from<T, U>(root: T, iter: MountIter<U>)
     Ok(ret)
 }
 
+/// Get a list of all mount points from `root` and beneath using a custom `BufRead`
+pub fn get_submounts_from<T, U>(root: T, iter: MountIter<U>)
+        -> Result<Vec<MountEntry>, ParseError> where T: AsRef<Path>, U: BufRead {
+    collect_matching(iter, |m| m.is_submount_of(&root))
+}
+
 /// Get a list of all mount points from `root` and beneath using */proc/mounts*
 pub fn get_submounts<T>(root: T) -> Result<Vec<MountEntry>, ParseError> where T: AsRef<Path> {
-    get_submounts_from(root, try!(MountIter::new_from_proc()))
+    get_submounts_from(root, MountIter::new_from_proc()?)
+}
+
+/// Get all mount points from `root` and beneath, keyed by mount point, using a custom `BufRead`
+///
+/// When several mounts are on the same mount point, only the topmost one (i.e. the last) is kept.
+pub fn get_submounts_map_from<T, U>(root: T, iter: MountIter<U>)
+        -> Result<HashMap<PathBuf, MountEntry>, ParseError> where T: AsRef<Path>, U: BufRead {
+    Ok(get_submounts_from(root, iter)?.into_iter().map(|m| (m.file.clone(), m)).collect())
+}
+
+/// Get all mount points from `root` and beneath, keyed by mount point, using */proc/mounts*
+pub fn get_submounts_map<T>(root: T) -> Result<HashMap<PathBuf, MountEntry>, ParseError> where T: AsRef<Path> {
+    get_submounts_map_from(root, MountIter::new_from_proc()?)
+}
+
+/// Get a list of all mount points from any of the `roots` and beneath using a custom `BufRead`
+pub fn get_submounts_many_from<T, U>(roots: &[T], iter: MountIter<U>)
+        -> Result<Vec<MountEntry>, ParseError> where T: AsRef<Path>, U: BufRead {
+    collect_matching(iter, |m| roots.iter().any(|root| m.is_submount_of(root)))
+}
+
+/// Get a list of all mount points from any of the `roots` and beneath using */proc/mounts*
+pub fn get_submounts_many<T>(roots: &[T]) -> Result<Vec<MountEntry>, ParseError> where T: AsRef<Path> {
+    get_submounts_many_from(roots, MountIter::new_from_proc()?)
 }
 
 /// Get the mount point for the `target` using a custom `BufRead`
@@ -173,7 +1120,7 @@ pub fn get_mount_from<T, U>(target: T, iter: MountIter<U>)
     let mut ret = None;
     for mount in iter {
         match mount {
-            Ok(m) => if target.as_ref().starts_with(&m.file) {
+            Ok(m) => if m.contains_path(&target) {
                 // Get the last entry
                 ret = Some(m);
             },
@@ -185,27 +1132,250 @@ pub fn get_mount_from<T, U>(target: T, iter: MountIter<U>)
 
 /// Get the mount point for the `target` using */proc/mounts*
 pub fn get_mount<T>(target: T) -> Result<Option<MountEntry>, ParseError> where T: AsRef<Path> {
-    get_mount_from(target, try!(MountIter::new_from_proc()))
+    get_mount_from(target, MountIter::new_from_proc()?)
 }
 
-/// Find the potential mount point providing readable or writable access to a path
+/// Get the first mount point matching `pred` using a custom `BufRead`
 ///
-/// Do not check the path existence but its potentially parent mount point.
-pub fn get_mount_writable<T>(target: T, writable: bool) -> Option<MountEntry> where T: AsRef<Path> {
-    match get_mount(target) {
-        Ok(Some(m)) => {
-            if !writable || m.mntops.contains(&MntOps::Write(writable)) {
-                Some(m)
-            } else {
-                None
-            }
+/// Stop reading as soon as a mount point matches.
+pub fn find_mount<U, F>(iter: MountIter<U>, pred: F)
+        -> Result<Option<MountEntry>, ParseError> where U: BufRead, F: Fn(&MountEntry) -> bool {
+    for mount in iter {
+        let m = mount?;
+        if pred(&m) {
+            return Ok(Some(m));
         }
-        _ => None,
     }
+    Ok(None)
 }
 
-pub trait VecMountEntry {
-    fn remove_overlaps<T>(self, exclude_files: &Vec<T>) -> Self where T: AsRef<Path>;
+/// Parsing errors with the number of their line
+pub type LineErrors = Vec<(usize, ParseError)>;
+
+/// Parse all the lines of `mtab`, returning the mount points and the line-numbered errors
+///
+/// Unlike `all_mounts_from()`, a malformed line doesn't prevent parsing the next ones.  The line
+/// numbers start at 0.  Failing to read `mtab` is an error on its own.
+pub fn parse_all<R>(mut mtab: R) -> Result<(Vec<MountEntry>, LineErrors), ParseError> where R: Read {
+    let mut content = vec!();
+    mtab.read_to_end(&mut content)?;
+    let mut mounts = vec!();
+    let mut errors = vec!();
+    for (nb, mount) in MountIter::new(content.as_slice()).enumerate() {
+        match mount {
+            Ok(m) => mounts.push(m),
+            Err(e) => errors.push((nb, e)),
+        }
+    }
+    Ok((mounts, errors))
+}
+
+/// Get a list of all mount points using a custom `BufRead`
+pub fn all_mounts_from<U>(iter: MountIter<U>) -> Result<Vec<MountEntry>, ParseError> where U: BufRead {
+    iter.collect()
+}
+
+/// Get a list of all mount points using */proc/mounts*
+pub fn all_mounts() -> Result<Vec<MountEntry>, ParseError> {
+    all_mounts_from(MountIter::new_from_proc()?)
+}
+
+/// Get the root mount point using a custom `BufRead`
+///
+/// If there are multiple mounts on `/`, return the topmost one (i.e. the last).
+pub fn get_root_mount_from<U>(iter: MountIter<U>) -> Result<Option<MountEntry>, ParseError> where U: BufRead {
+    Ok(collect_matching(iter, |m| m.file == Path::new("/"))?.pop())
+}
+
+/// Get the root mount point using */proc/mounts*
+pub fn get_root_mount() -> Result<Option<MountEntry>, ParseError> {
+    get_root_mount_from(MountIter::new_from_proc()?)
+}
+
+/// Get the on-disk root mount point using a custom `BufRead`
+///
+/// Same as `get_root_mount_from` but the initramfs is skipped, which only matters if nothing is
+/// mounted over it yet (e.g. early boot); otherwise the topmost mount on `/` is already the real one.
+pub fn get_real_root_mount_from<U>(iter: MountIter<U>) -> Result<Option<MountEntry>, ParseError> where U: BufRead {
+    Ok(collect_matching(iter, |m| m.file == Path::new("/") && !m.is_initramfs_rootfs())?.pop())
+}
+
+/// Get the on-disk root mount point using */proc/mounts*
+pub fn get_real_root_mount() -> Result<Option<MountEntry>, ParseError> {
+    get_real_root_mount_from(MountIter::new_from_proc()?)
+}
+
+/// Get the mount points of every process from a procfs mounted on `proc_root`
+///
+/// Processes which vanish or can't be read are skipped, but malformed mounts files are errors.
+/// Processes sharing a mount namespace can be grouped with `namespace_fingerprint`.
+pub fn scan_all_namespaces_from<T>(proc_root: T) -> Result<HashMap<u32, Vec<MountEntry>>, ParseError>
+        where T: AsRef<Path> {
+    let mut ret = HashMap::new();
+    for dir in fs::read_dir(proc_root)? {
+        let dir = dir?;
+        let pid = match dir.file_name().to_str().and_then(|name| name.parse().ok()) {
+            Some(pid) => pid,
+            None => continue,
+        };
+        let mounts = MountIter::new_from_proc_root(dir.path())
+            .and_then(|iter| iter.collect::<Result<Vec<_>, _>>());
+        match mounts {
+            Ok(mounts) => { ret.insert(pid, mounts); }
+            Err(ref e) if e.is_io() => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(ret)
+}
+
+/// Get the mount points of every process using */proc*
+pub fn scan_all_namespaces() -> Result<HashMap<u32, Vec<MountEntry>>, ParseError> {
+    scan_all_namespaces_from(PROC_ROOT)
+}
+
+/// Find the potential mount point providing readable or writable access to a path
+///
+/// Do not check the path existence but its potentially parent mount point.
+pub fn get_mount_writable<T>(target: T, writable: bool) -> Option<MountEntry> where T: AsRef<Path> {
+    match get_mount(target) {
+        Ok(Some(m)) => {
+            if !writable || m.mntops.contains(&MntOps::Write(writable)) {
+                Some(m)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Order mount points so that they can be unmounted front-to-back
+///
+/// Each mount point comes before its parent, as deduced for `sort_tree` (e.g. a later mount on an
+/// ancestor path comes before the mount points it hides), and the last mounted children come
+/// first.  The first root mount, if any, ends up last.
+// FIXME: Doesn't work for moved mounts: they don't change order
+pub fn unmount_order(entries: Vec<MountEntry>) -> Vec<MountEntry> {
+    let order = tree_preorder(&entries);
+    let mut entries: Vec<Option<MountEntry>> = entries.into_iter().map(Some).collect();
+    order.into_iter().rev().filter_map(|(i, _)| entries[i].take()).collect()
+}
+
+/// Get the index of the parent of each mount point, according to their paths
+///
+/// The parent of a mount point is the last mount before it on the same path or, failing that, on
+/// the closest ancestor path: a later mount on an ancestor path hides it instead of holding it.
+/// Only without any such previous mount (e.g. a moved root) is the last mount point on the
+/// closest ancestor path picked.
+fn tree_parents(entries: &[MountEntry]) -> Vec<Option<usize>> {
+    let mut last: HashMap<&Path, usize> = HashMap::new();
+    for (i, mount) in entries.iter().enumerate() {
+        last.insert(&mount.file, i);
+    }
+    let mut previous: HashMap<&Path, usize> = HashMap::new();
+    entries.iter().enumerate().map(|(i, mount)| {
+        let parent = mount.file.ancestors().filter_map(|path| previous.get(path).cloned()).next()
+            .or_else(|| mount.file.ancestors().skip(1).filter_map(|path| last.get(path).cloned()).next());
+        previous.insert(&mount.file, i);
+        parent
+    }).collect()
+}
+
+/// Get the preorder traversal of the mount tree as `(index, depth)` pairs
+fn tree_preorder(entries: &[MountEntry]) -> Vec<(usize, usize)> {
+    let mut roots = vec!();
+    let mut children = vec!(vec!(); entries.len());
+    for (i, parent) in tree_parents(entries).into_iter().enumerate() {
+        match parent {
+            Some(p) => children[p].push(i),
+            None => roots.push(i),
+        }
+    }
+    // Explicit stack: a stack of over-mounts is as deep as its number of mounts
+    let mut stack: Vec<(usize, usize)> = roots.into_iter().rev().map(|i| (i, 0)).collect();
+    let mut ret = Vec::with_capacity(entries.len());
+    while let Some((i, depth)) = stack.pop() {
+        ret.push((i, depth));
+        stack.extend(children[i].iter().rev().map(|&c| (c, depth + 1)));
+    }
+    ret
+}
+
+/// Sort mount points so that each one is immediately followed by its children (preorder)
+///
+/// The parent/child links are deduced from the mount point paths and the mount order.
+pub fn sort_tree(entries: Vec<MountEntry>) -> Vec<MountEntry> {
+    let order = tree_preorder(&entries);
+    let mut entries: Vec<Option<MountEntry>> = entries.into_iter().map(Some).collect();
+    order.into_iter().filter_map(|(i, _)| entries[i].take()).collect()
+}
+
+/// Sort mount points in a total order independent of the mount order, for reproducible snapshots
+///
+/// Mount points are ordered by path, filesystem type, source and then options.
+pub fn canonical_sort(entries: &mut [MountEntry]) {
+    entries.sort_by_cached_key(|m| (m.file.clone(), m.vfstype.clone(), m.spec.clone(), m.mount_options_string()));
+}
+
+/// Render the mount tree like `findmnt`, one line per mount point in preorder
+///
+/// The parent/child links are deduced as for `sort_tree`.
+pub fn findmnt_tree(entries: &[MountEntry]) -> String {
+    let order = tree_preorder(entries);
+    // Whether each mount point is the last of its siblings, i.e. no sibling follows before a
+    // shallower mount point
+    let mut is_last = vec!(false; order.len());
+    let mut followed: Vec<bool> = vec!();
+    for (pos, &(_, depth)) in order.iter().enumerate().rev() {
+        followed.resize(depth + 1, false);
+        is_last[pos] = !followed[depth];
+        followed[depth] = true;
+    }
+    // For each ancestor level, whether it was the last of its siblings
+    let mut lasts: Vec<bool> = vec!();
+    let mut ret = String::new();
+    for (pos, &(i, depth)) in order.iter().enumerate() {
+        let mut prefix = String::new();
+        if depth > 0 {
+            let last = is_last[pos];
+            lasts.truncate(depth - 1);
+            for &ancestor_last in lasts.iter() {
+                prefix.push_str(if ancestor_last { "  " } else { "│ " });
+            }
+            prefix.push_str(if last { "└─" } else { "├─" });
+            lasts.push(last);
+        }
+        ret.push_str(&entries[i].display_findmnt_tree(&prefix));
+        ret.push('\n');
+    }
+    ret
+}
+
+/// Get a fingerprint of a mount namespace, independent of the mount points order
+///
+/// The mount point, filesystem type and source of each entry are hashed with FNV-1a, which is
+/// stable across Rust versions and runs.
+pub fn namespace_fingerprint(entries: &[MountEntry]) -> u64 {
+    let mut keys = entries.iter().map(|m| (path_as_bytes(&m.file), m.vfstype.as_bytes(), m.spec.as_bytes()))
+        .collect::<Vec<_>>();
+    keys.sort();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for (file, vfstype, spec) in keys {
+        // The NUL byte separates the fields
+        for byte in file.iter().chain(&[0]).chain(vfstype).chain(&[0]).chain(spec).chain(&[0]) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+pub trait VecMountEntry {
+    #[allow(clippy::ptr_arg)]
+    fn remove_overlaps<T>(self, exclude_files: &Vec<T>) -> Self where T: AsRef<Path>;
+    /// Get the sorted mount points, without duplicates
+    fn only_mount_points(&self) -> Vec<PathBuf>;
 }
 
 impl VecMountEntry for Vec<MountEntry> {
@@ -220,8 +1390,8 @@ impl VecMountEntry for Vec<MountEntry> {
             }
             let mut has_overlaps = false;
             'filter: for mount_sorted in sorted.iter() {
-                if exclude_files.iter().skip_while(|x|
-                       AsRef::<Path>::as_ref(&mount_sorted.file) != x.as_ref()).next().is_some() {
+                if exclude_files.iter().any(|x|
+                       AsRef::<Path>::as_ref(&mount_sorted.file) == x.as_ref()) {
                     continue 'filter;
                 }
                 // Check for mount overlaps
@@ -237,19 +1407,48 @@ impl VecMountEntry for Vec<MountEntry> {
         sorted.reverse();
         sorted
     }
+
+    fn only_mount_points(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = self.iter().map(|m| m.file.clone()).collect();
+        files.sort();
+        files.dedup();
+        files
+    }
 }
 
 
+/// Reconstruct the mounts file line, with the spec and mount point escaped
+impl fmt::Display for MountEntry {
+    fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {
+        write!(out, "{} {} {} {} {} {}", escape_octal(self.spec.as_bytes()),
+               escape_octal(&path_as_bytes(&self.file)), self.vfstype, self.mount_options_string(),
+               self.freq as c_int, self.passno.unwrap_or(0))?;
+        for field in self.extra_fields.iter() {
+            write!(out, " {}", field)?;
+        }
+        Ok(())
+    }
+}
+
 impl fmt::Debug for MountEntry {
     fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {
-        write!(out, "MountEntry {{ spec: {:?}, file: {:?}, vfstype: {:?}, mntops: {:?}, freq: {:?}, passno: {:?} }}",
-               self.spec, self.file.display(), self.vfstype, self.mntops, self.freq, self.passno)
+        write!(out, "MountEntry {{ spec: {:?}, file: {:?}, vfstype: {:?}, mntops: {:?}, freq: {:?}, passno: {:?}, extra_fields: {:?}, raw: {:?} }}",
+               self.spec, self.file.display(), self.vfstype, self.mntops, self.freq, self.passno, self.extra_fields, self.raw)
+    }
+}
+
+// The raw line is not part of the mount point identity
+impl PartialEq for MountEntry {
+    fn eq(&self, other: &MountEntry) -> bool {
+        self.spec == other.spec && self.file == other.file && self.vfstype == other.vfstype &&
+            self.mntops == other.mntops && self.freq == other.freq && self.passno == other.passno &&
+            self.extra_fields == other.extra_fields
     }
 }
 
 impl PartialOrd for MountEntry {
     fn partial_cmp(&self, other: &MountEntry) -> Option<Ordering> {
-        self.file.partial_cmp(&other.file)
+        Some(self.cmp(other))
     }
 }
 
@@ -261,49 +1460,443 @@ impl Ord for MountEntry {
 
 
 pub struct MountIter<T> {
-    lines: Enumerate<Lines<T>>,
+    mtab: T,
+    nb: usize,
+    keep_raw: bool,
 }
 
 impl<T> MountIter<T> where T: BufRead {
     pub fn new(mtab: T) -> MountIter<T> {
         MountIter {
-            lines: mtab.lines().enumerate(),
+            mtab,
+            nb: 0,
+            keep_raw: false,
+        }
+    }
+
+    /// Same as `next()` but read the line into `buf`, which can then be reused between calls
+    pub fn next_into(&mut self, buf: &mut Vec<u8>) -> Option<Result<MountEntry, ParseError>> {
+        let nb = match self.read_line(buf)? {
+            Ok(nb) => nb,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(match MountEntry::from_bytes(buf) {
+            Ok(mut m) => {
+                if self.keep_raw {
+                    m.raw = Some(String::from_utf8_lossy(buf.trim_ascii()).into_owned());
+                }
+                Ok(m)
+            },
+            Err(e) => Err(ParseError::at_line(nb, e)),
+        })
+    }
+
+    /// Read the next line into `buf`, without its newline, and get its number
+    fn read_line(&mut self, buf: &mut Vec<u8>) -> Option<Result<usize, ParseError>> {
+        buf.clear();
+        let nb = self.nb;
+        match self.mtab.read_until(b'\n', buf) {
+            Ok(0) => return None,
+            Ok(_) => {},
+            Err(e) => return Some(Err(From::from(e))),
+        }
+        self.nb += 1;
+        if buf.last() == Some(&b'\n') {
+            buf.pop();
+        }
+        Some(Ok(nb))
+    }
+
+    /// Keep the source line of each entry in its `raw` field
+    pub fn keep_raw(mut self, keep: bool) -> MountIter<T> {
+        self.keep_raw = keep;
+        self
+    }
+
+    /// Skip the virtual filesystems and those not backed by a device, but not the parsing errors
+    pub fn skip_pseudo(self) -> SkipPseudo<T> {
+        SkipPseudo {
+            iter: self,
         }
     }
+
+    /// Check that all the lines can be parsed and get the number of entries
+    ///
+    /// The lines are checked in place, in a reused buffer, without building the entries.
+    pub fn count_entries(mut self) -> Result<usize, ParseError> {
+        let mut buf = vec!();
+        let mut count = 0;
+        while let Some(nb) = self.read_line(&mut buf) {
+            let nb = nb?;
+            LineFields::new(&buf).map_err(|e| ParseError::at_line(nb, e))?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Get all the mount points, stopping at the first error
+    ///
+    /// This is the same as collecting into a `Result<Vec<_>, _>`; see `parse_all()` to get the
+    /// mount points of all the valid lines instead.
+    pub fn try_collect(self) -> Result<Vec<MountEntry>, ParseError> {
+        self.collect()
+    }
 }
 
 impl MountIter<BufReader<File>> {
     pub fn new_from_proc() -> Result<MountIter<BufReader<File>>, ParseError> {
-        let file = try!(File::open(PROC_MOUNTS));
+        MountIter::new_from_proc_root(PROC_ROOT)
+    }
+
+    /// Read the *mounts* file from a procfs mounted on `proc_root`
+    pub fn new_from_proc_root<T>(proc_root: T) -> Result<MountIter<BufReader<File>>, ParseError>
+            where T: AsRef<Path> {
+        let file = File::open(proc_root.as_ref().join("mounts"))?;
         Ok(MountIter::new(BufReader::new(file)))
     }
+
+    /// Read the mount points from an already opened file descriptor (e.g. */proc/<pid>/mounts*)
+    ///
+    /// # Safety
+    ///
+    /// The iterator takes ownership of `fd`, which is closed when the iterator is dropped: it must
+    /// be an open file descriptor which is not used nor closed elsewhere.
+    #[cfg(unix)]
+    pub unsafe fn from_fd(fd: RawFd) -> MountIter<BufReader<File>> {
+        MountIter::new(BufReader::new(File::from_raw_fd(fd)))
+    }
+}
+
+#[cfg(feature = "flate2")]
+impl MountIter<BufReader<GzDecoder<File>>> {
+    /// Read a gzip-compressed mounts file (e.g. from a support bundle), decompressing on the fly
+    pub fn new_from_gz_path<T>(path: T) -> Result<MountIter<BufReader<GzDecoder<File>>>, ParseError>
+            where T: AsRef<Path> {
+        let file = File::open(path)?;
+        Ok(MountIter::new(BufReader::new(GzDecoder::new(file))))
+    }
 }
 
 impl<T> Iterator for MountIter<T> where T: BufRead {
     type Item = Result<MountEntry, ParseError>;
 
     fn next(&mut self) -> Option<<Self as Iterator>::Item> {
-        match self.lines.next() {
-            Some((nb, line)) => Some(match line {
-                Ok(line) => match <MountEntry as FromStr>::from_str(line.as_ref()) {
-                    Ok(m) => Ok(m),
-                    Err(e) => Err(ParseError::new(format!("Failed at line {}: {}", nb, e))),
-                },
-                Err(e) => Err(From::from(e)),
-            }),
-            None => None,
+        self.next_into(&mut vec!())
+    }
+}
+
+/// Iterator over the device-backed mount points only, cf. `MountIter::skip_pseudo`
+pub struct SkipPseudo<T> {
+    iter: MountIter<T>,
+}
+
+impl<T> Iterator for SkipPseudo<T> where T: BufRead {
+    type Item = Result<MountEntry, ParseError>;
+
+    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
+        self.iter.by_ref().find(|m| match *m {
+            Ok(ref m) => !m.is_pseudo_fs() && m.source_device_path().is_some(),
+            Err(_) => true,
+        })
+    }
+}
+
+/// Set of constraints that a mount point must all match
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct MountQuery {
+    vfstype: Option<String>,
+    writable: Option<bool>,
+    under: Option<PathBuf>,
+    spec: Option<String>,
+}
+
+impl MountQuery {
+    pub fn new() -> MountQuery {
+        Default::default()
+    }
+
+    /// Match the filesystem type
+    pub fn vfstype<T>(mut self, vfstype: T) -> MountQuery where T: Into<String> {
+        self.vfstype = Some(vfstype.into());
+        self
+    }
+
+    /// Match read-write (`true`) or read-only (`false`) mount points
+    pub fn writable(mut self, writable: bool) -> MountQuery {
+        self.writable = Some(writable);
+        self
+    }
+
+    /// Match the mount points on `root` or beneath it
+    pub fn under<T>(mut self, root: T) -> MountQuery where T: AsRef<Path> {
+        self.under = Some(root.as_ref().to_path_buf());
+        self
+    }
+
+    /// Match the source device
+    pub fn spec<T>(mut self, spec: T) -> MountQuery where T: Into<String> {
+        self.spec = Some(spec.into());
+        self
+    }
+
+    /// Check if `mount` matches all the constraints
+    pub fn matches(&self, mount: &MountEntry) -> bool {
+        self.vfstype.as_ref().is_none_or(|v| mount.contains(&MountParam::VfsType(v))) &&
+            self.writable.is_none_or(|w| mount.flags().write == w) &&
+            self.under.as_ref().is_none_or(|r| mount.is_submount_of(r)) &&
+            self.spec.as_ref().is_none_or(|s| mount.contains(&MountParam::Spec(s)))
+    }
+
+    /// Get all the matching mount points using a custom `BufRead`
+    pub fn run_from<T>(&self, iter: MountIter<T>) -> Result<Vec<MountEntry>, ParseError> where T: BufRead {
+        let mut ret = vec!();
+        for mount in iter {
+            let mount = mount?;
+            if self.matches(&mount) {
+                ret.push(mount);
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Get all the matching mount points using */proc/mounts*
+    pub fn run(&self) -> Result<Vec<MountEntry>, ParseError> {
+        self.run_from(MountIter::new_from_proc()?)
+    }
+}
+
+/// In-memory copy of a mounts file which can be queried several times
+#[derive(Clone, Debug)]
+pub struct MountSnapshot {
+    content: Vec<u8>,
+}
+
+impl MountSnapshot {
+    pub fn new<T>(mut mtab: T) -> Result<MountSnapshot, ParseError> where T: Read {
+        let mut content = vec!();
+        mtab.read_to_end(&mut content)?;
+        Ok(MountSnapshot {
+            content,
+        })
+    }
+
+    pub fn new_from_proc() -> Result<MountSnapshot, ParseError> {
+        MountSnapshot::new_from_proc_root(PROC_ROOT)
+    }
+
+    /// Read the *mounts* file from a procfs mounted on `proc_root`
+    pub fn new_from_proc_root<T>(proc_root: T) -> Result<MountSnapshot, ParseError> where T: AsRef<Path> {
+        MountSnapshot::new(File::open(proc_root.as_ref().join("mounts"))?)
+    }
+
+    /// Iterate over the mount points of the snapshot
+    pub fn iter(&self) -> MountIter<&[u8]> {
+        MountIter::new(&self.content)
+    }
+
+    /// Get a list of all mount points from `root` and beneath
+    pub fn get_submounts<T>(&self, root: T) -> Result<Vec<MountEntry>, ParseError> where T: AsRef<Path> {
+        get_submounts_from(root, self.iter())
+    }
+
+    /// Get the mount point for the `target`
+    pub fn get_mount<T>(&self, target: T) -> Result<Option<MountEntry>, ParseError> where T: AsRef<Path> {
+        get_mount_from(target, self.iter())
+    }
+
+    /// Get the mount point for the `target` and the path of `target` relative to it
+    pub fn resolve_path<T>(&self, target: T)
+            -> Result<Option<(MountEntry, PathBuf)>, ParseError> where T: AsRef<Path> {
+        Ok(self.get_mount(&target)?.map(|m| {
+            let relative = target.as_ref().strip_prefix(&m.file).unwrap_or(Path::new("")).to_path_buf();
+            (m, relative)
+        }))
+    }
+}
+
+/// Parsed mount points which can be queried several times
+///
+/// It can be collected from a `MountIter`, e.g. `iter.collect::<Result<MountTable, _>>()?`.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct MountTable {
+    entries: Vec<MountEntry>,
+}
+
+impl MountTable {
+    pub fn new_from_proc() -> Result<MountTable, ParseError> {
+        MountIter::new_from_proc()?.collect()
+    }
+
+    /// Get the mount points, in mount order
+    pub fn entries(&self) -> &[MountEntry] {
+        &self.entries
+    }
+
+    pub fn into_vec(self) -> Vec<MountEntry> {
+        self.entries
+    }
+
+    /// Get the mount point for the `target`, i.e. the last one containing it
+    pub fn get_mount<T>(&self, target: T) -> Option<&MountEntry> where T: AsRef<Path> {
+        self.entries.iter().rev().find(|m| m.contains_path(&target))
+    }
+
+    /// Get a list of all mount points from `root` and beneath
+    pub fn get_submounts<T>(&self, root: T) -> Vec<&MountEntry> where T: AsRef<Path> {
+        self.entries.iter().filter(|m| m.is_submount_of(&root)).collect()
+    }
+
+    /// Get the mount points in preorder with their depth in the tree, cf. `sort_tree`
+    pub fn tree(&self) -> Vec<(&MountEntry, usize)> {
+        tree_preorder(&self.entries).into_iter().map(|(i, depth)| (&self.entries[i], depth)).collect()
+    }
+}
+
+impl FromIterator<MountEntry> for MountTable {
+    fn from_iter<I>(iter: I) -> MountTable where I: IntoIterator<Item = MountEntry> {
+        MountTable {
+            entries: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Mount points added and removed between two reads of a mounts file
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct MountDiff {
+    pub added: Vec<MountEntry>,
+    pub removed: Vec<MountEntry>,
+}
+
+/// Monitor a mounts file by fully re-reading it, as procfs files are rewritten instead of appended
+#[derive(Clone, Debug)]
+pub struct MountReReader {
+    path: PathBuf,
+    previous: Vec<MountEntry>,
+}
+
+impl MountReReader {
+    /// Monitor the mounts file at `path`, starting from an empty mount list
+    pub fn new<T>(path: T) -> MountReReader where T: AsRef<Path> {
+        MountReReader {
+            path: path.as_ref().to_path_buf(),
+            previous: vec!(),
         }
     }
+
+    pub fn new_from_proc() -> MountReReader {
+        MountReReader::new_from_proc_root(PROC_ROOT)
+    }
+
+    /// Monitor the *mounts* file from a procfs mounted on `proc_root`
+    pub fn new_from_proc_root<T>(proc_root: T) -> MountReReader where T: AsRef<Path> {
+        MountReReader::new(proc_root.as_ref().join("mounts"))
+    }
+
+    /// Re-read the mounts file, returning all the mount points and the changes since the last read
+    pub fn refresh(&mut self) -> Result<(Vec<MountEntry>, MountDiff), ParseError> {
+        let file = File::open(&self.path)?;
+        let current = MountIter::new(BufReader::new(file)).collect::<Result<Vec<_>, _>>()?;
+        // Identical mount points (e.g. repeated bind mounts) are compared by number of occurrences
+        let count = |mounts: &[MountEntry], mount: &MountEntry| mounts.iter().filter(|m| *m == mount).count();
+        let missing = |from: &[MountEntry], other: &[MountEntry]| from.iter().enumerate()
+            .filter(|&(i, m)| count(&from[..=i], m) > count(other, m)).map(|(_, m)| m.clone()).collect();
+        let diff = MountDiff {
+            added: missing(&current, &self.previous),
+            removed: missing(&self.previous, &current),
+        };
+        self.previous = current.clone();
+        Ok((current, diff))
+    }
 }
 
 
 #[cfg(test)]
-mod test {
-    use std::fs::File;
-    use std::io::{BufReader, BufRead, Cursor};
+pub(crate) mod test {
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::{self, BufReader, BufRead, Cursor, Read};
+    use std::ops::Deref;
+    #[cfg(unix)]
+    use std::os::unix::ffi::OsStrExt;
+    #[cfg(unix)]
+    use std::os::unix::fs::symlink;
     use std::path::{Path, PathBuf};
     use std::str::FromStr;
-    use super::{DumpField, MntOps, MountEntry, MountIter, MountParam, get_mount_from, get_submounts_from};
+    use error::LineError;
+    use super::{AutofsInfo, AutofsMode, DumpField, FsFamily, MatchOptions, MntOps, MountEntry, MountFlags, MountIter,
+                MountParam, MountQuery, MountReReader, MountSnapshot, MountTable, OpFamily, OverlayDirs, Propagation, SecurityFlags,
+                SizeSpec, VecMountEntry, VfsType};
+    use super::{all_mounts_from, canonical_order, canonical_sort, collect_matching, fill_defaults, find_mount, findmnt_tree,
+                get_mount_from, get_real_root_mount_from, get_root_mount_from, get_submounts_from,
+                get_submounts_many_from, get_submounts_map_from, mntops_eq, namespace_fingerprint, parse_all,
+                parse_size_option, scan_all_namespaces, scan_all_namespaces_from, sort_tree, unmount_order};
+
+    fn sample() -> Cursor<&'static [u8]> {
+        Cursor::new(b"\
+            sysfs /sys sysfs rw,seclabel,nosuid,nodev,noexec,relatime 0 0\n\
+            proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0\n\
+            devtmpfs /dev devtmpfs rw,seclabel,nosuid,size=7898068k,nr_inodes=1974517,mode=755 0 0\n\
+            securityfs /sys/kernel/security securityfs rw,nosuid,nodev,noexec,relatime 0 0\n\
+            tmpfs /dev/shm tmpfs rw,seclabel,nosuid,nodev 0 0\n\
+            devpts /dev/pts devpts rw,seclabel,nosuid,noexec,relatime,gid=5,mode=620,ptmxmode=000 0 0\n\
+            tmpfs /run tmpfs rw,seclabel,nosuid,nodev,mode=755 0 0\n\
+            tmpfs /sys/fs/cgroup tmpfs ro,seclabel,nosuid,nodev,noexec,mode=755 0 0\n\
+            cgroup /sys/fs/cgroup/systemd cgroup rw,nosuid,nodev,noexec,relatime,xattr,name=systemd 0 0\n\
+            pstore /sys/fs/pstore pstore rw,seclabel,nosuid,nodev,noexec,relatime 0 0\n\
+            cgroup /sys/fs/cgroup/cpuset cgroup rw,nosuid,nodev,noexec,relatime,cpuset 0 0\n\
+            configfs /sys/kernel/config configfs rw,relatime 0 0\n\
+            /dev/mapper/luks-3f6b0024-3d3b-4a8d-a1c5-53e3c8a4ea53 / xfs rw,seclabel,relatime,attr2,inode64,noquota 0 0\n\
+            selinuxfs /sys/fs/selinux selinuxfs rw,relatime 0 0\n\
+            systemd-1 /proc/sys/fs/binfmt_misc autofs rw,relatime,fd=32,pgrp=1,timeout=0,minproto=5,maxproto=5,direct 0 0\n\
+            debugfs /sys/kernel/debug debugfs rw,seclabel,relatime 0 0\n\
+            hugetlbfs /dev/hugepages hugetlbfs rw,seclabel,relatime 0 0\n\
+            mqueue /dev/mqueue mqueue rw,seclabel,relatime 0 0\n\
+            tmpfs /tmp tmpfs rw,seclabel 0 0\n\
+            nfsd /proc/fs/nfsd nfsd rw,relatime 0 0\n\
+            /dev/sda1 /boot ext4 rw,seclabel,relatime,data=ordered 0 0\n\
+            sunrpc /var/lib/nfs/rpc_pipefs rpc_pipefs rw,relatime 0 0\n\
+            tmpfs /run/user/1000 tmpfs rw,seclabel,nosuid,nodev,relatime,size=1582224k,mode=700,uid=1000,gid=1000 0 0\n\
+            gvfsd-fuse /run/user/1000/gvfs fuse.gvfsd-fuse rw,nosuid,nodev,relatime,user_id=1000,group_id=1000 0 0\n\
+            /dev/mmcblk0p1 /run/media/user/SDCARD vfat rw,nosuid,nodev,relatime,uid=1000,gid=1000,fmask=0022,dmask=0077,codepage=437,iocharset=ascii,shortname=mixed,showexec,utf8,flush,errors=remount-ro,uhelper=udisks2 0 0\n\
+        ".as_ref())
+    }
+
+    /// Get the mount point on `file` from `sample()`
+    fn sample_mount(file: &str) -> MountEntry {
+        all_mounts_from(MountIter::new(sample())).unwrap().into_iter().find(|m| m.file == Path::new(file)).unwrap()
+    }
+
+    /// Temporary directory, removed when dropped even if the test fails
+    pub struct TempDir(PathBuf);
+
+    impl TempDir {
+        pub fn new(name: &str) -> TempDir {
+            let path = env::temp_dir().join(format!("mnt-rs-{}-{}", name, ::std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDir(path)
+        }
+    }
+
+    impl Deref for TempDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl AsRef<Path> for TempDir {
+        fn as_ref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
 
     #[test]
     fn test_line_root() {
@@ -314,12 +1907,16 @@ mod test {
             mntops: vec!(MntOps::Write(true)),
             freq: DumpField::Ignore,
             passno: None,
+            extra_fields: vec!(),
+            raw: None,
         };
         let from_str = <MountEntry as FromStr>::from_str;
         assert_eq!(from_str("rootfs / rootfs rw 0 0"), Ok(root_ref.clone()));
         assert_eq!(from_str("rootfs   / rootfs rw 0 0"), Ok(root_ref.clone()));
         assert_eq!(from_str("rootfs	/ rootfs rw 0 0"), Ok(root_ref.clone()));
         assert_eq!(from_str("rootfs / rootfs rw, 0 0"), Ok(root_ref.clone()));
+        assert_eq!(from_str("rootfs\t / \t rootfs\t rw \t0\t \t0"), Ok(root_ref.clone()));
+        assert_eq!(from_str(" \trootfs / rootfs rw 0 0\t "), Ok(root_ref.clone()));
     }
 
     #[test]
@@ -331,9 +1928,15 @@ mod test {
             mntops: vec!(MntOps::Exec(false), MntOps::Write(true)),
             freq: DumpField::Ignore,
             passno: None,
+            extra_fields: vec!(),
+            raw: None,
         };
         let from_str = <MountEntry as FromStr>::from_str;
         assert_eq!(from_str("rootfs / rootfs noexec,rw 0 0"), Ok(root_ref.clone()));
+        assert_eq!(from_str("rootfs / rootfs noexec,,rw 0 0"), Ok(root_ref.clone()));
+        assert_eq!(from_str("rootfs / rootfs , 0 0").map(|m| m.mntops), Ok(vec!()));
+        // An empty field can't be told apart from a missing one
+        assert_eq!(from_str("rootfs / rootfs  0 0"), Err(LineError::MissingPassno));
     }
 
     fn test_file<T>(path: T) -> Result<(), String> where T: AsRef<Path> {
@@ -367,6 +1970,9 @@ mod test {
         assert!(from_str("rootfs foo rootfs rw 0 0").is_err());
         // Should fail for a swap pseudo-mount
         assert!(from_str("/dev/mapper/swap none swap sw 0 0").is_err());
+        assert_eq!(from_str("tmpfs run/user tmpfs rw 0 0"), Err(LineError::InvalidFilePath("run/user".to_string())));
+        assert_eq!(MountEntry::from_bytes(b"tmpfs caf\xe9 tmpfs rw 0 0"),
+                   Err(LineError::InvalidFilePath("caf\u{fffd}".to_string())));
     }
 
     #[test]
@@ -389,7 +1995,9 @@ mod test {
             vfstype: "ext4".to_string(),
             mntops: vec![Write(true), RelAtime(true), Extra("data=ordered".to_string())],
             freq: Ignore,
-            passno: None
+            passno: None,
+            extra_fields: vec!(),
+            raw: None,
         };
         let mount_root = MountEntry {
             spec: "rootfs".to_string(),
@@ -397,7 +2005,9 @@ mod test {
             vfstype: "rootfs".to_string(),
             mntops: vec![Write(true)],
             freq: Ignore,
-            passno: None
+            passno: None,
+            extra_fields: vec!(),
+            raw: None,
         };
         let mount_sysfs = MountEntry {
             spec: "sysfs".to_string(),
@@ -405,7 +2015,9 @@ mod test {
             vfstype: "sysfs".to_string(),
             mntops: vec![Write(true), Suid(false), Dev(false), Exec(false), RelAtime(true)],
             freq: Ignore,
-            passno: None
+            passno: None,
+            extra_fields: vec!(),
+            raw: None,
         };
         let mount_tmp = MountEntry {
             spec: "tmpfs".to_string(),
@@ -413,7 +2025,9 @@ mod test {
             vfstype: "tmpfs".to_string(),
             mntops: vec![Write(false), Suid(false), Dev(false), Exec(false), Extra("mode=755".to_string())],
             freq: Ignore,
-            passno: None
+            passno: None,
+            extra_fields: vec!(),
+            raw: None,
         };
         let mounts_all = vec!(
             mount_root.clone(),
@@ -425,7 +2039,9 @@ mod test {
                 vfstype: "devtmpfs".to_string(),
                 mntops: vec![Write(true), RelAtime(true), Extra("size=10240k".to_string()), Extra("nr_inodes=505357".to_string()), Extra("mode=755".to_string())],
                 freq: Ignore,
-                passno: None
+                passno: None,
+                extra_fields: vec!(),
+                raw: None,
             },
             MountEntry {
                 spec: "tmpfs".to_string(),
@@ -433,7 +2049,9 @@ mod test {
                 vfstype: "tmpfs".to_string(),
                 mntops: vec![Write(true), Suid(false), RelAtime(true), Extra("size=809928k".to_string()), Extra("mode=755".to_string())],
                 freq: Ignore,
-                passno: None
+                passno: None,
+                extra_fields: vec!(),
+                raw: None,
             },
             mount_vartmp.clone()
         );
@@ -449,19 +2067,19 @@ mod test {
         assert_eq!(get_mount_from("/var/", mounts).ok(), Some(Some(mount_root.clone())));
 
         // search
-        let mut mounts = MountIter::new(buf.clone()).map(|m| m.ok().unwrap());;
+        let mut mounts = MountIter::new(buf.clone()).map(|m| m.ok().unwrap());
         assert_eq!(mounts.find(|m|
                m.contains(&MountParam::Spec("rootfs"))
             ).unwrap(), mount_root.clone());
-        let mut mounts = MountIter::new(buf.clone()).map(|m| m.ok().unwrap());;
+        let mut mounts = MountIter::new(buf.clone()).map(|m| m.ok().unwrap());
         assert_eq!(mounts.find(|m|
                 m.contains(&MountParam::File(Path::new("/")))
             ).unwrap(), mount_root.clone());
-        let mut mounts = MountIter::new(buf.clone()).map(|m| m.ok().unwrap());;
+        let mut mounts = MountIter::new(buf.clone()).map(|m| m.ok().unwrap());
         assert_eq!(mounts.find(|m|
                 m.contains(&MountParam::VfsType("tmpfs"))
             ).unwrap(), mount_tmp.clone());
-        let mut mounts = MountIter::new(buf.clone()).map(|m| m.ok().unwrap());;
+        let mut mounts = MountIter::new(buf.clone()).map(|m| m.ok().unwrap());
         let mnt_ops = [MntOps::Write(true), MntOps::Suid(false), MntOps::Dev(false), MntOps::Exec(false)];
         assert_eq!(mounts.find(|m| {
                 mnt_ops.iter().all( |o| m.contains(&MountParam::MntOps(o)) )
@@ -476,4 +2094,1106 @@ mod test {
                 m.contains(&MountParam::PassNo(&None))
             ).collect::<Vec<_>>(), mounts_all.clone());
     }
+
+    #[test]
+    fn test_unmount_order() {
+        let buf = Cursor::new(b"\
+            rootfs / rootfs rw 0 0\n\
+            sysfs /sys sysfs rw,nosuid,nodev,noexec,relatime 0 0\n\
+            proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0\n\
+            securityfs /sys/kernel/security securityfs rw,nosuid,nodev,noexec,relatime 0 0\n\
+            /dev/dm-0 / ext4 rw,relatime,errors=remount-ro,data=ordered 0 0\n\
+        ".as_ref());
+        let mounts = MountIter::new(buf).map(|m| m.unwrap()).collect();
+        let files = unmount_order(mounts).into_iter().map(|m| (m.spec, m.file)).collect::<Vec<_>>();
+        // The last root mount hides the previous mount points
+        assert_eq!(files, vec!(
+            ("/dev/dm-0".to_string(), PathBuf::from("/")),
+            ("proc".to_string(), PathBuf::from("/proc")),
+            ("securityfs".to_string(), PathBuf::from("/sys/kernel/security")),
+            ("sysfs".to_string(), PathBuf::from("/sys")),
+            ("rootfs".to_string(), PathBuf::from("/")),
+        ));
+
+        // An ancestor mounted over a child hides it, so the ancestor must be unmounted first
+        let buf = Cursor::new(b"/dev/sda1 / ext4 rw 0 0\ntmpfs /a/b tmpfs rw 0 0\ntmpfs /a tmpfs rw 0 0\n".as_ref());
+        let mounts = MountIter::new(buf).map(|m| m.unwrap()).collect();
+        let files = unmount_order(mounts).into_iter().map(|m| m.file).collect::<Vec<_>>();
+        assert_eq!(files, vec!(PathBuf::from("/a"), PathBuf::from("/a/b"), PathBuf::from("/")));
+        let buf = Cursor::new(b"/dev/sda1 / ext4 rw 0 0\ntmpfs /a tmpfs rw 0 0\ntmpfs /a/b tmpfs rw 0 0\n\
+                                tmpfs /a tmpfs ro 0 0\n".as_ref());
+        let mounts = MountIter::new(buf).map(|m| m.unwrap()).collect();
+        let ops = unmount_order(mounts).into_iter().map(|m| (m.file.clone(), m.mount_options_string())).collect::<Vec<_>>();
+        assert_eq!(ops, vec!(
+            (PathBuf::from("/a"), "ro".to_string()),
+            (PathBuf::from("/a/b"), "rw".to_string()),
+            (PathBuf::from("/a"), "rw".to_string()),
+            (PathBuf::from("/"), "rw".to_string()),
+        ));
+    }
+
+    #[test]
+    fn test_all_mounts() {
+        let mounts = all_mounts_from(MountIter::new(sample())).unwrap();
+        assert_eq!(mounts.len(), 25);
+        assert_eq!(mounts[0].file, PathBuf::from("/sys"));
+        assert_eq!(mounts[24].vfstype, "vfat");
+
+        let buf = Cursor::new(b"rootfs / rootfs rw 0 0\nrootfs foo rootfs rw 0 0\n".as_ref());
+        assert!(all_mounts_from(MountIter::new(buf)).is_err());
+    }
+
+    #[test]
+    fn test_source_device_path() {
+        let boot = sample_mount("/boot");
+        assert_eq!(boot.source_device_path(), Some(Path::new("/dev/sda1")));
+        let sys = sample_mount("/sys");
+        assert_eq!(sys.source_device_path(), None);
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn test_new_from_gz_path() {
+        use super::flate2::Compression;
+        use super::flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let mut content = vec!();
+        sample().read_to_end(&mut content).unwrap();
+        let dir = TempDir::new("gz");
+        let path = dir.join("mounts.gz");
+        let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+        encoder.write_all(&content).unwrap();
+        encoder.finish().unwrap();
+        let mounts = MountIter::new_from_gz_path(&path).unwrap().try_collect().unwrap();
+        assert_eq!(mounts, all_mounts_from(MountIter::new(sample())).unwrap());
+        fs::remove_file(&path).unwrap();
+        assert!(MountIter::new_from_gz_path(&path).err().is_some_and(|e| e.is_io()));
+    }
+
+    #[test]
+    fn test_submounts_many() {
+        let mounts = get_submounts_many_from(&["/dev", "/run"], MountIter::new(sample())).unwrap();
+        let files = mounts.iter().map(|m| m.file.to_str().unwrap()).collect::<Vec<_>>();
+        assert_eq!(files, vec!("/dev", "/dev/shm", "/dev/pts", "/run", "/dev/hugepages", "/dev/mqueue",
+                               "/run/user/1000", "/run/user/1000/gvfs", "/run/media/user/SDCARD"));
+    }
+
+    #[test]
+    fn test_raw_line() {
+        let mounts = all_mounts_from(MountIter::new(sample())).unwrap();
+        assert!(mounts.iter().all(|m| m.raw.is_none()));
+
+        let raw_mounts = all_mounts_from(MountIter::new(sample()).keep_raw(true)).unwrap();
+        assert_eq!(raw_mounts[1].raw, Some("proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0".to_string()));
+        assert!(format!("{:?}", raw_mounts[1]).contains("raw: Some(\"proc /proc proc"));
+        assert_eq!(raw_mounts, mounts);
+    }
+
+    #[test]
+    fn test_mntops_eq() {
+        use super::MntOps::*;
+
+        assert!(mntops_eq(&[Write(true), Dev(false)], &[Dev(false), Write(true)]));
+        assert!(mntops_eq(&[Extra("mode=755".to_string()), Exec(false)],
+                          &[Exec(false), Extra("mode=755".to_string())]));
+        assert!(!mntops_eq(&[Write(true), Dev(false)], &[Write(false), Dev(false)]));
+        assert!(!mntops_eq(&[Extra("mode=755".to_string())], &[Extra("mode=700".to_string())]));
+        assert!(!mntops_eq(&[Write(true), Write(true), Dev(false)], &[Write(true), Dev(false), Dev(false)]));
+        assert!(!mntops_eq(&[Write(true)], &[Write(true), Dev(false)]));
+    }
+
+    #[test]
+    fn test_contains_all() {
+        let params = [
+            MountParam::MntOps(&MntOps::Write(true)),
+            MountParam::MntOps(&MntOps::Suid(false)),
+            MountParam::MntOps(&MntOps::Dev(false)),
+            MountParam::MntOps(&MntOps::Exec(false)),
+        ];
+        let mut mounts = MountIter::new(sample()).map(|m| m.unwrap());
+        assert_eq!(mounts.find(|m| m.contains_all(&params)).map(|m| m.file), Some(PathBuf::from("/sys")));
+
+        let params = [MountParam::VfsType("ext4"), MountParam::VfsType("vfat")];
+        let mounts = MountIter::new(sample()).map(|m| m.unwrap());
+        assert_eq!(mounts.filter(|m| m.contains_any(&params)).map(|m| m.file).collect::<Vec<_>>(),
+                   vec!(PathBuf::from("/boot"), PathBuf::from("/run/media/user/SDCARD")));
+    }
+
+    #[test]
+    fn test_mount_options_string() {
+        let dev = sample_mount("/dev");
+        assert_eq!(dev.mount_options_string(), "rw,seclabel,nosuid,size=7898068k,nr_inodes=1974517,mode=755");
+        let sys = sample_mount("/sys");
+        assert_eq!(sys.mount_options_string(), "rw,seclabel,nosuid,nodev,noexec,relatime");
+    }
+
+    // Serve one line per fill_buf() call to count the consumed lines
+    struct LineReader {
+        lines: Vec<&'static [u8]>,
+        pos: usize,
+        offset: usize,
+    }
+
+    impl Read for LineReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let size = {
+                let line = self.fill_buf()?;
+                let size = line.len().min(buf.len());
+                buf[..size].copy_from_slice(&line[..size]);
+                size
+            };
+            self.consume(size);
+            Ok(size)
+        }
+    }
+
+    impl BufRead for LineReader {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            Ok(match self.lines.get(self.pos) {
+                Some(line) => &line[self.offset..],
+                None => &[],
+            })
+        }
+
+        fn consume(&mut self, amt: usize) {
+            self.offset += amt;
+            if self.pos < self.lines.len() && self.offset >= self.lines[self.pos].len() {
+                self.pos += 1;
+                self.offset = 0;
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_mount() {
+        let mut reader = LineReader {
+            lines: vec!(
+                b"sysfs /sys sysfs rw,nosuid,nodev,noexec,relatime 0 0\n",
+                b"proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0\n",
+                b"tmpfs /run tmpfs rw,nosuid,nodev,mode=755 0 0\n",
+                b"bad line\n",
+            ),
+            pos: 0,
+            offset: 0,
+        };
+        let found = find_mount(MountIter::new(&mut reader), |m| m.vfstype == "proc").unwrap();
+        assert_eq!(found.map(|m| m.file), Some(PathBuf::from("/proc")));
+        assert_eq!(reader.pos, 2);
+
+        let found = find_mount(MountIter::new(sample()), |m| m.vfstype == "nfs4").unwrap();
+        assert_eq!(found, None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_non_utf8_file() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let buf = Cursor::new(b"/dev/sdb1 /media/caf\xe9 vfat rw,relatime 0 0\n/dev/sdb2 /media/\xff\xfe ext4 rw 0 0\n".as_ref());
+        let mounts = all_mounts_from(MountIter::new(buf)).unwrap();
+        assert_eq!(mounts[0].file, PathBuf::from(OsStr::from_bytes(b"/media/caf\xe9")));
+        assert_eq!(mounts[1].file, PathBuf::from(OsStr::from_bytes(b"/media/\xff\xfe")));
+        assert_eq!(mounts[1].vfstype, "ext4");
+        assert!(MountEntry::from_bytes(b"/dev/sdb1 /media vf\xe9t rw 0 0").is_err());
+    }
+
+    #[test]
+    fn test_fstype_family() {
+        assert_eq!(sample_mount("/boot").fstype_family(), FsFamily::Ext);
+        assert_eq!(sample_mount("/run/media/user/SDCARD").fstype_family(), FsFamily::Fat);
+        assert_eq!(sample_mount("/run/user/1000/gvfs").fstype_family(), FsFamily::Fuse);
+        assert_eq!(sample_mount("/sys/fs/cgroup/cpuset").fstype_family(), FsFamily::Cgroup);
+        assert_eq!(sample_mount("/").fstype_family(), FsFamily::Other("xfs"));
+    }
+
+    #[test]
+    fn test_fuse() {
+        let gvfs = sample_mount("/run/user/1000/gvfs");
+        assert!(gvfs.is_fuse());
+        assert_eq!(gvfs.fuse_subtype(), Some("gvfsd-fuse"));
+        let root = sample_mount("/");
+        assert!(!root.is_fuse());
+        assert_eq!(root.fuse_subtype(), None);
+        let fuse = MountEntry::from_str("/dev/sdc1 /mnt fuseblk rw 0 0").unwrap();
+        assert!(fuse.is_fuse());
+        assert_eq!(fuse.fuse_subtype(), None);
+    }
+
+    #[test]
+    fn test_collect_matching() {
+        let mounts = collect_matching(MountIter::new(sample()), |m| m.mntops.contains(&MntOps::Suid(false))).unwrap();
+        assert_eq!(mounts.len(), 14);
+        assert!(mounts.iter().all(|m| m.contains(&MountParam::MntOps(&MntOps::Suid(false)))));
+        assert!(!mounts.iter().any(|m| m.file == Path::new("/")));
+
+        let buf = Cursor::new(b"rootfs / rootfs rw 0 0\nrootfs / rootfs rw 0\n".as_ref());
+        assert!(collect_matching(MountIter::new(buf), |_| false).is_err());
+    }
+
+    #[test]
+    fn test_size_option() {
+        assert_eq!(parse_size_option("1582224k"), Some(SizeSpec::Bytes(1582224 * 1024)));
+        assert_eq!(parse_size_option("7898068k"), Some(SizeSpec::Bytes(7898068 * 1024)));
+        assert_eq!(parse_size_option("2G"), Some(SizeSpec::Bytes(2 << 30)));
+        assert_eq!(parse_size_option("4096"), Some(SizeSpec::Bytes(4096)));
+        assert_eq!(parse_size_option("50%"), Some(SizeSpec::Percent(50)));
+        assert_eq!(parse_size_option("k"), None);
+        assert_eq!(parse_size_option(""), None);
+        assert_eq!(parse_size_option("99999999999e"), None);
+
+        let size = |file: &str| sample_mount(file).tmpfs_size_bytes();
+        assert_eq!(size("/run/user/1000"), Some(1582224 * 1024));
+        assert_eq!(size("/dev"), Some(7898068 * 1024));
+        assert_eq!(size("/tmp"), None);
+        let tmp = MountEntry::from_str("tmpfs /tmp tmpfs rw,size=50% 0 0").unwrap();
+        assert_eq!(tmp.tmpfs_size_bytes(), None);
+    }
+
+    #[test]
+    fn test_vfstype_ignore_case() {
+        let mut mounts = MountIter::new(sample()).map(|m| m.unwrap());
+        assert_eq!(mounts.find(|m| m.contains(&MountParam::VfsTypeIgnoreCase("XFS"))).map(|m| m.file),
+                   Some(PathBuf::from("/")));
+        let mut mounts = MountIter::new(sample()).map(|m| m.unwrap());
+        assert_eq!(mounts.find(|m| m.contains(&MountParam::VfsType("XFS"))), None);
+    }
+
+    #[test]
+    fn test_summary() {
+        let boot = sample_mount("/boot");
+        assert_eq!(boot.summary(), "ext4 /dev/sda1 on /boot (rw,seclabel,relatime,data=ordered)");
+    }
+
+    #[test]
+    fn test_count_entries() {
+        assert_eq!(MountIter::new(sample()).count_entries().ok(), Some(25));
+        let buf = Cursor::new(b"rootfs / rootfs rw 0 0\nrootfs / rootfs rw 0 3x\n".as_ref());
+        assert!(MountIter::new(buf).count_entries().is_err());
+        // Same checks as the full parsing
+        for line in &[&b"a\\377 / tmpfs rw 0 0"[..], b"a rel tmpfs rw 0 0", b"a /\\040b tmpfs rw 0 0 \xff",
+                      b"a \\057b tmpfs rw 0 0", b"a /b tmpfs rw 2 0"] {
+            let count = MountIter::new(Cursor::new(*line)).count_entries();
+            let parsed = MountIter::new(Cursor::new(*line)).try_collect();
+            assert_eq!(count.map_err(|e| e.to_string()), parsed.map(|m| m.len()).map_err(|e| e.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let snapshot = MountSnapshot::new(sample()).unwrap();
+        assert_eq!(snapshot.get_submounts("/sys/fs/cgroup").unwrap().len(), 3);
+        assert_eq!(snapshot.get_mount("/run/user/1000/gvfs/foo").unwrap().map(|m| m.vfstype),
+                   Some("fuse.gvfsd-fuse".to_string()));
+        assert_eq!(snapshot.iter().count(), 25);
+    }
+
+    #[test]
+    fn test_pseudo_fs() {
+        let pseudo = |file: &str| sample_mount(file).is_pseudo_fs();
+        assert!(pseudo("/sys"));
+        assert!(pseudo("/proc"));
+        assert!(pseudo("/dev"));
+        assert!(pseudo("/proc/sys/fs/binfmt_misc"));
+        assert!(pseudo("/var/lib/nfs/rpc_pipefs"));
+        assert!(!pseudo("/"));
+        assert!(!pseudo("/boot"));
+        assert!(!pseudo("/run/media/user/SDCARD"));
+        assert!(MountEntry::from_str("none /mnt foofs rw 0 0").unwrap().is_pseudo_fs());
+        assert!(MountEntry::from_str("foofs /mnt foofs rw 0 0").unwrap().is_pseudo_fs());
+    }
+
+    #[test]
+    fn test_flags() {
+        let sys = sample_mount("/sys");
+        assert_eq!(sys.flags(), MountFlags {
+            write: true,
+            exec: false,
+            suid: false,
+            dev: false,
+            atime: true,
+            relatime: true,
+            diratime: true,
+        });
+        let tmp = MountEntry::from_str("tmpfs /tmp tmpfs rw,ro,noatime 0 0").unwrap();
+        assert_eq!(tmp.flags(), MountFlags { write: false, atime: false, ..MountFlags::default() });
+    }
+
+    #[test]
+    fn test_try_into() {
+        use std::convert::TryInto;
+
+        let root: MountEntry = "rootfs / rootfs rw 0 0".try_into().unwrap();
+        assert_eq!(root.file, PathBuf::from("/"));
+        let bad: Result<MountEntry, _> = "rootfs / rootfs rw 0".try_into();
+        assert_eq!(bad, Err(LineError::MissingPassno));
+        let op: MntOps = "nosuid".try_into().unwrap();
+        assert_eq!(op, MntOps::Suid(false));
+    }
+
+    #[test]
+    fn test_root_mount() {
+        let root = get_root_mount_from(MountIter::new(sample())).unwrap().unwrap();
+        assert_eq!(root.vfstype, "xfs");
+        assert_eq!(root.spec, "/dev/mapper/luks-3f6b0024-3d3b-4a8d-a1c5-53e3c8a4ea53");
+
+        let buf = Cursor::new(b"rootfs / rootfs rw 0 0\n/dev/dm-0 / ext4 rw 0 0\nproc /proc proc rw 0 0\n".as_ref());
+        assert_eq!(get_root_mount_from(MountIter::new(buf)).unwrap().map(|m| m.spec), Some("/dev/dm-0".to_string()));
+        let buf = Cursor::new(b"proc /proc proc rw 0 0\n".as_ref());
+        assert_eq!(get_root_mount_from(MountIter::new(buf)).unwrap(), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_from_fd() {
+        use std::io::Write;
+        use std::os::unix::io::FromRawFd;
+        use super::libc;
+
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        {
+            let mut writer = unsafe { File::from_raw_fd(fds[1]) };
+            writer.write_all(sample().get_ref()).unwrap();
+        }
+        let mounts = unsafe { MountIter::from_fd(fds[0]) };
+        assert_eq!(mounts.count_entries().ok(), Some(25));
+    }
+
+    #[test]
+    fn test_overlay_dirs() {
+        let overlay = MountEntry::from_str("overlay /var/lib/docker/overlay2/42/merged overlay \
+            rw,relatime,lowerdir=/l/a\\054b:/l/c\\:d:/l/e,upperdir=/u\\054p,workdir=/w 0 0").unwrap();
+        assert_eq!(overlay.overlay_dirs(), Some(OverlayDirs {
+            lower: vec!(PathBuf::from("/l/a,b"), PathBuf::from("/l/c:d"), PathBuf::from("/l/e")),
+            upper: Some(PathBuf::from("/u,p")),
+            work: Some(PathBuf::from("/w")),
+        }));
+        let overlay = MountEntry::from_str("overlay /mnt overlay ro,lowerdir+=/a,lowerdir+=/b 0 0").unwrap();
+        assert_eq!(overlay.overlay_dirs(), Some(OverlayDirs {
+            lower: vec!(PathBuf::from("/a"), PathBuf::from("/b")),
+            upper: None,
+            work: None,
+        }));
+        let root = MountEntry::from_str("/dev/sda1 / ext4 rw 0 0").unwrap();
+        assert_eq!(root.overlay_dirs(), None);
+    }
+
+    #[test]
+    fn test_mntops_sync_mand_silent() {
+        let ops = [
+            ("sync", MntOps::Sync(true)),
+            ("async", MntOps::Sync(false)),
+            ("dirsync", MntOps::DirSync),
+            ("mand", MntOps::Mand(true)),
+            ("nomand", MntOps::Mand(false)),
+            ("silent", MntOps::Silent(true)),
+            ("loud", MntOps::Silent(false)),
+        ];
+        for &(token, ref op) in ops.iter() {
+            assert_eq!(MntOps::from_str(token).as_ref(), Ok(op));
+            assert_eq!(op.to_string(), token);
+        }
+    }
+
+    #[test]
+    fn test_mntops_lazytime_symfollow() {
+        let ops = [
+            ("lazytime", MntOps::LazyTime(true)),
+            ("nolazytime", MntOps::LazyTime(false)),
+            ("symfollow", MntOps::SymFollow(true)),
+            ("nosymfollow", MntOps::SymFollow(false)),
+        ];
+        for &(token, ref op) in ops.iter() {
+            assert_eq!(MntOps::from_str(token).as_ref(), Ok(op));
+            assert_eq!(op.to_string(), token);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_resolve_path() {
+        let snapshot = MountSnapshot::new(sample()).unwrap();
+        let (mount, relative) = snapshot.resolve_path("/run/user/1000/gvfs/foo").unwrap().unwrap();
+        assert_eq!(mount.vfstype, "fuse.gvfsd-fuse");
+        assert_eq!(relative, PathBuf::from("foo"));
+        let (mount, relative) = snapshot.resolve_path("/etc/hostname").unwrap().unwrap();
+        assert_eq!(mount.vfstype, "xfs");
+        assert_eq!(relative, PathBuf::from("etc/hostname"));
+        let (mount, relative) = snapshot.resolve_path("/boot").unwrap().unwrap();
+        assert_eq!(mount.vfstype, "ext4");
+        assert_eq!(relative, PathBuf::new());
+        assert!(snapshot.resolve_path("relative").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_submount_contains_path() {
+        let cgroup = sample_mount("/sys/fs/cgroup");
+        assert!(cgroup.is_submount_of("/sys"));
+        assert!(cgroup.is_submount_of("/sys/fs/cgroup"));
+        assert!(!cgroup.is_submount_of("/sys/fs/cgroup/cpuset"));
+        assert!(!cgroup.is_submount_of("/sy"));
+        assert!(cgroup.contains_path("/sys/fs/cgroup/cpuset"));
+        assert!(cgroup.contains_path("/sys/fs/cgroup"));
+        assert!(!cgroup.contains_path("/sys/fs"));
+        assert!(!cgroup.contains_path("/sys/fs/cgroupfoo"));
+    }
+
+    #[test]
+    fn test_next_into() {
+        let mut mounts = MountIter::new(sample()).keep_raw(true);
+        let mut buf = vec!();
+        let mut reused = vec!();
+        while let Some(mount) = mounts.next_into(&mut buf) {
+            reused.push(mount.unwrap());
+        }
+        assert_eq!(reused, all_mounts_from(MountIter::new(sample())).unwrap());
+        assert_eq!(reused[24].raw.as_ref().map(|r| r.starts_with("/dev/mmcblk0p1 ")), Some(true));
+
+        let buf = Cursor::new(b"rootfs / rootfs rw 0 0\nrootfs / rootfs rw 0".as_ref());
+        let mut mounts = MountIter::new(buf);
+        let mut line = vec!();
+        assert!(mounts.next_into(&mut line).unwrap().is_ok());
+        assert_eq!(mounts.next_into(&mut line).unwrap().unwrap_err().to_string(),
+                   "Mount parsing: Failed at line 1: Missing field #6 (passno)");
+        assert!(mounts.next_into(&mut line).is_none());
+    }
+
+    #[test]
+    fn test_vfstype_enum() {
+        let vfstype = |file: &str| sample_mount(file).vfstype_enum();
+        assert_eq!(vfstype("/"), VfsType::Xfs);
+        assert_eq!(vfstype("/run/media/user/SDCARD"), VfsType::Vfat);
+        assert_eq!(vfstype("/run/user/1000/gvfs"), VfsType::Fuse);
+        assert_eq!(vfstype("/sys/kernel/security"), VfsType::Other("securityfs".to_string()));
+    }
+
+    #[test]
+    fn test_option_delta() {
+        let before = MountEntry::from_str("/dev/sda1 /boot ext4 rw,nodev,relatime 0 0").unwrap();
+        let after = MountEntry::from_str("/dev/sda1 /boot ext4 ro,nodev,relatime,noexec 0 0").unwrap();
+        assert_eq!(before.option_delta(&after),
+                   (vec!(MntOps::Write(false), MntOps::Exec(false)), vec!(MntOps::Write(true))));
+        assert_eq!(before.option_delta(&before), (vec!(), vec!()));
+    }
+
+    #[test]
+    fn test_new_from_proc_root() {
+        use std::io::Write;
+
+        let proc_root = TempDir::new("proc-root");
+        File::create(proc_root.join("mounts")).unwrap().write_all(sample().get_ref()).unwrap();
+        assert_eq!(MountIter::new_from_proc_root(&proc_root).unwrap().count_entries().ok(), Some(25));
+        let snapshot = MountSnapshot::new_from_proc_root(&proc_root).unwrap();
+        assert_eq!(snapshot.iter().count(), 25);
+
+        let err = MountIter::new_from_proc_root(proc_root.join("missing")).err().unwrap();
+        assert!(err.is_io());
+    }
+
+    #[test]
+    fn test_parse_all() {
+        let buf = Cursor::new(b"\
+            rootfs / rootfs rw 0 0\n\
+            sysfs sys sysfs rw 0 0\n\
+            proc /proc proc rw,nosuid 0 0\n\
+            tmpfs /tmp tmpfs rw 0\n\
+        ".as_ref());
+        let (mounts, errors) = parse_all(buf).unwrap();
+        assert_eq!(mounts.iter().map(|m| m.file.to_str().unwrap()).collect::<Vec<_>>(), vec!("/", "/proc"));
+        assert_eq!(errors.iter().map(|&(nb, _)| nb).collect::<Vec<_>>(), vec!(1, 3));
+        assert!(errors.iter().all(|(_, e)| e.is_parse()));
+
+        struct Unreadable;
+        impl Read for Unreadable {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::PermissionDenied, "unreadable"))
+            }
+        }
+        assert!(parse_all(Unreadable).err().unwrap().is_io());
+    }
+
+    #[test]
+    fn test_mntops_mount_command() {
+        let parse = MntOps::parse_list;
+        assert_eq!(parse("bind,ro"), vec!(MntOps::Bind, MntOps::Write(false)));
+        assert_eq!(parse("rbind,move"), vec!(MntOps::RBind, MntOps::Move));
+        assert_eq!(parse("rshared"), vec!(MntOps::Propagation(Propagation::Shared, true)));
+        assert_eq!(parse("slave,runbindable"), vec!(MntOps::Propagation(Propagation::Slave, false),
+                                                    MntOps::Propagation(Propagation::Unbindable, true)));
+        for token in ["bind", "rbind", "move", "shared", "rshared", "slave", "rslave", "private", "rprivate",
+                      "unbindable", "runbindable"].iter() {
+            assert_eq!(MntOps::from_str(token).unwrap().to_string(), *token);
+        }
+    }
+
+    #[test]
+    fn test_stable_key() {
+        let before = all_mounts_from(MountIter::new(sample())).unwrap();
+        let after = all_mounts_from(MountIter::new(Cursor::new(b"\
+            /dev/sda1 /boot ext4 ro,seclabel,relatime,data=ordered 0 0\n\
+            tmpfs /tmp tmpfs rw,seclabel,nosuid 0 0\n\
+            tmpfs /mnt tmpfs rw 0 0\n\
+        ".as_ref()))).unwrap();
+        let matched = after.iter().filter_map(|a| {
+            before.iter().find(|b| b.stable_key() == a.stable_key()).map(|b| (b.file.clone(), b != a))
+        }).collect::<Vec<_>>();
+        assert_eq!(matched, vec!((PathBuf::from("/boot"), true), (PathBuf::from("/tmp"), true)));
+    }
+
+    #[test]
+    fn test_shares_source_with() {
+        let mounts = all_mounts_from(MountIter::new(Cursor::new(b"\
+            /dev/sda2 / ext4 rw,relatime 0 0\n\
+            tmpfs /tmp tmpfs rw 0 0\n\
+            /dev/sda1 /boot ext4 rw,relatime 0 0\n\
+            /dev/sda2 /srv/chroot ext4 ro,relatime 0 0\n\
+            tmpfs /run tmpfs rw 0 0\n\
+        ".as_ref()))).unwrap();
+        let sharing = |file: &str| {
+            let mount = mounts.iter().find(|m| m.file == Path::new(file)).unwrap();
+            mounts.iter().filter(|m| mount.shares_source_with(m)).map(|m| m.file.to_str().unwrap()).collect::<Vec<_>>()
+        };
+        assert_eq!(sharing("/"), vec!("/", "/srv/chroot"));
+        assert_eq!(sharing("/boot"), vec!("/boot"));
+        assert!(sharing("/tmp").is_empty());
+    }
+
+    #[test]
+    fn test_idmapped() {
+        let mount = MountEntry::from_str("/dev/sdb1 /home/user ext4 rw,relatime,idmapped 0 0").unwrap();
+        assert_eq!(mount.mntops, vec!(MntOps::Write(true), MntOps::RelAtime(true), MntOps::IdMapped));
+        assert!(mount.is_idmapped());
+        assert_eq!(mount.mount_options_string(), "rw,relatime,idmapped");
+        let mounts = all_mounts_from(MountIter::new(sample())).unwrap();
+        assert!(!mounts.iter().any(|m| m.is_idmapped()));
+    }
+
+    #[test]
+    fn test_sort_tree() {
+        let mounts = sort_tree(all_mounts_from(MountIter::new(sample())).unwrap());
+        let files = mounts.iter().map(|m| m.file.to_str().unwrap()).collect::<Vec<_>>();
+        assert_eq!(files, vec!(
+            "/",
+            "/sys",
+            "/sys/kernel/security",
+            "/sys/fs/cgroup",
+            "/sys/fs/cgroup/systemd",
+            "/sys/fs/cgroup/cpuset",
+            "/sys/fs/pstore",
+            "/sys/kernel/config",
+            "/sys/fs/selinux",
+            "/sys/kernel/debug",
+            "/proc",
+            "/proc/sys/fs/binfmt_misc",
+            "/proc/fs/nfsd",
+            "/dev",
+            "/dev/shm",
+            "/dev/pts",
+            "/dev/hugepages",
+            "/dev/mqueue",
+            "/run",
+            "/run/user/1000",
+            "/run/user/1000/gvfs",
+            "/run/media/user/SDCARD",
+            "/tmp",
+            "/boot",
+            "/var/lib/nfs/rpc_pipefs",
+        ));
+
+        let buf = Cursor::new(b"rootfs / rootfs rw 0 0\n/dev/sda1 / ext4 rw 0 0\nproc /proc proc rw 0 0\n".as_ref());
+        let mounts = sort_tree(all_mounts_from(MountIter::new(buf)).unwrap());
+        assert_eq!(mounts.iter().map(|m| m.spec.as_str()).collect::<Vec<_>>(), vec!("rootfs", "/dev/sda1", "proc"));
+
+        // A mount point hidden by a later over-mount of its parent stays beneath its parent
+        let buf = Cursor::new(b"/dev/sda1 / ext4 rw 0 0\ntmpfs /a tmpfs rw 0 0\ntmpfs /a/b tmpfs rw 0 0\n\
+                                tmpfs /a tmpfs ro 0 0\n".as_ref());
+        let mounts = all_mounts_from(MountIter::new(buf)).unwrap();
+        assert_eq!(sort_tree(mounts.clone()), mounts);
+        assert_eq!(findmnt_tree(&mounts), "\
+            /  /dev/sda1  ext4  rw\n\
+            └─/a  tmpfs  tmpfs  rw\n  \
+              ├─/a/b  tmpfs  tmpfs  rw\n  \
+              └─/a  tmpfs  tmpfs  ro\n");
+    }
+
+    #[test]
+    fn test_namespace_fingerprint() {
+        let mounts = all_mounts_from(MountIter::new(sample())).unwrap();
+        let mut reversed = mounts.clone();
+        reversed.reverse();
+        assert_eq!(namespace_fingerprint(&mounts), namespace_fingerprint(&reversed));
+        assert_eq!(namespace_fingerprint(&mounts), namespace_fingerprint(&sort_tree(mounts.clone())));
+        assert!(namespace_fingerprint(&mounts) != namespace_fingerprint(&mounts[1..]));
+        assert_eq!(namespace_fingerprint(&[]), 0xcbf2_9ce4_8422_2325);
+
+        // Only the field boundaries differ
+        let a = MountEntry::from_str("ab /x c rw 0 0").unwrap();
+        let b = MountEntry::from_str("b /x ca rw 0 0").unwrap();
+        assert!(namespace_fingerprint(&[a]) != namespace_fingerprint(&[b]));
+    }
+
+    #[test]
+    fn test_mntops_tokens() {
+        use super::MntOps::*;
+        use super::Propagation::*;
+
+        let table = [
+            (Atime(true), "atime"), (Atime(false), "noatime"),
+            (DirAtime(true), "diratime"), (DirAtime(false), "nodiratime"),
+            (RelAtime(true), "relatime"), (RelAtime(false), "norelatime"),
+            (Dev(true), "dev"), (Dev(false), "nodev"),
+            (Exec(true), "exec"), (Exec(false), "noexec"),
+            (Suid(true), "suid"), (Suid(false), "nosuid"),
+            (Write(true), "rw"), (Write(false), "ro"),
+            (Sync(true), "sync"), (Sync(false), "async"),
+            (DirSync, "dirsync"),
+            (Mand(true), "mand"), (Mand(false), "nomand"),
+            (Silent(true), "silent"), (Silent(false), "loud"),
+            (LazyTime(true), "lazytime"), (LazyTime(false), "nolazytime"),
+            (SymFollow(true), "symfollow"), (SymFollow(false), "nosymfollow"),
+            (IdMapped, "idmapped"),
+            (Bind, "bind"), (RBind, "rbind"), (Move, "move"),
+            (Propagation(Shared, false), "shared"), (Propagation(Shared, true), "rshared"),
+            (Propagation(Slave, false), "slave"), (Propagation(Slave, true), "rslave"),
+            (Propagation(Private, false), "private"), (Propagation(Private, true), "rprivate"),
+            (Propagation(Unbindable, false), "unbindable"), (Propagation(Unbindable, true), "runbindable"),
+            (Extra("mode=755".to_string()), "mode=755"),
+        ];
+        for &(ref op, token) in table.iter() {
+            assert_eq!(op.to_string(), token);
+            assert_eq!(MntOps::from_str(token).as_ref(), Ok(op));
+        }
+    }
+
+    #[test]
+    fn test_submounts_map() {
+        let mounts = get_submounts_map_from("/sys", MountIter::new(sample())).unwrap();
+        assert_eq!(mounts.len(), 9);
+        assert_eq!(mounts.get(Path::new("/sys")).map(|m| m.vfstype.as_str()), Some("sysfs"));
+        assert!(!mounts.contains_key(Path::new("/proc")));
+
+        let buf = Cursor::new(b"rootfs / rootfs rw 0 0\n/dev/sda1 / ext4 rw 0 0\n".as_ref());
+        let mounts = get_submounts_map_from("/", MountIter::new(buf)).unwrap();
+        assert_eq!(mounts.get(Path::new("/")).map(|m| m.vfstype.as_str()), Some("ext4"));
+    }
+
+    #[test]
+    fn test_autofs_info() {
+        let mounts: Vec<_> = MountIter::new(sample()).map(|m| m.unwrap()).collect();
+        let autofs: Vec<_> = mounts.iter().filter(|m| m.is_autofs()).collect();
+        assert_eq!(autofs.len(), 1);
+        assert_eq!(autofs[0].file, PathBuf::from("/proc/sys/fs/binfmt_misc"));
+        assert_eq!(autofs[0].autofs_info(), Some(AutofsInfo {
+            fd: Some(32),
+            pgrp: Some(1),
+            timeout: Some(0),
+            mode: Some(AutofsMode::Direct),
+        }));
+        let root = MountEntry::from_str("/dev/sda1 / ext4 rw 0 0").unwrap();
+        assert_eq!(root.autofs_info(), None);
+    }
+
+    #[test]
+    fn test_escaped_display() {
+        let line = "/dev/sdb1 /media/my\\040disk vfat rw,nosuid 0 2";
+        let mount = MountEntry::from_str(line).unwrap();
+        assert_eq!(mount.human_path(), Path::new("/media/my disk"));
+        assert_eq!(mount.to_string(), line);
+        assert_eq!(MountEntry::from_str(&mount.to_string()), Ok(mount));
+        let mount = MountEntry::from_str("a\\134b /mnt/c\\011d tmpfs rw 0 0").unwrap();
+        assert_eq!(mount.spec, "a\\b");
+        assert_eq!(mount.to_string(), "a\\134b /mnt/c\\011d tmpfs rw 0 0");
+
+        let mount = MountEntry::from_str("/dev/sdb1 /media/caf\u{e9} vfat rw 0 0").unwrap();
+        assert_eq!(mount.human_path(), Path::new("/media/caf\u{e9}"));
+        assert_eq!(mount.to_string(), "/dev/sdb1 /media/caf\u{e9} vfat rw 0 0");
+        assert_eq!(MountEntry::from_str(&mount.to_string()), Ok(mount));
+        #[cfg(unix)]
+        {
+            let mount = MountEntry::from_bytes(b"/dev/sdb1 /media/caf\xe9 vfat rw 0 0").unwrap();
+            assert_eq!(mount.file.as_os_str().as_bytes(), b"/media/caf\xe9");
+            assert_eq!(mount.to_string(), "/dev/sdb1 /media/caf\\351 vfat rw 0 0");
+            assert_eq!(MountEntry::from_str(&mount.to_string()), Ok(mount));
+        }
+    }
+
+    #[test]
+    fn test_rereader() {
+        let dir = TempDir::new("rereader");
+        let path = dir.join("mounts");
+        fs::write(&path, "/dev/sda1 / ext4 rw 0 0\nproc /proc proc rw 0 0\n").unwrap();
+        let mut reader = MountReReader::new(&path);
+        let (mounts, diff) = reader.refresh().unwrap();
+        assert_eq!(mounts.len(), 2);
+        assert_eq!(diff.added, mounts);
+        assert!(diff.removed.is_empty());
+
+        fs::write(&path, "/dev/sda1 / ext4 ro 0 0\nproc /proc proc rw 0 0\ntmpfs /tmp tmpfs rw 0 0\n").unwrap();
+        let (mounts, diff) = reader.refresh().unwrap();
+        assert_eq!(mounts.len(), 3);
+        assert_eq!(diff.added.iter().map(|m| m.file.to_str().unwrap()).collect::<Vec<_>>(), vec!("/", "/tmp"));
+        assert_eq!(diff.removed.len(), 1);
+        assert!(diff.removed[0].contains(&MountParam::MntOps(&MntOps::Write(true))));
+
+        let (_, diff) = reader.refresh().unwrap();
+        assert_eq!(diff, Default::default());
+
+        // One of two identical bind mounts disappears
+        let bind = "/dev/sda1 /mnt ext4 rw 0 0\n";
+        fs::write(&path, format!("/dev/sda1 / ext4 rw 0 0\n{}{}", bind, bind)).unwrap();
+        let (_, diff) = reader.refresh().unwrap();
+        assert_eq!(diff.added.iter().map(|m| m.file.to_str().unwrap()).collect::<Vec<_>>(), vec!("/", "/mnt", "/mnt"));
+        fs::write(&path, format!("/dev/sda1 / ext4 rw 0 0\n{}", bind)).unwrap();
+        let (_, diff) = reader.refresh().unwrap();
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.removed, vec!(MountEntry::from_str(bind.trim_end()).unwrap()));
+    }
+
+    #[test]
+    fn test_mount_query() {
+        let query = MountQuery::new().vfstype("tmpfs").writable(true).under("/run");
+        let mounts = query.run_from(MountIter::new(sample())).unwrap();
+        assert_eq!(mounts.iter().map(|m| m.file.to_str().unwrap()).collect::<Vec<_>>(),
+                   vec!("/run", "/run/user/1000"));
+        let query = MountQuery::new().writable(false);
+        let mounts = query.run_from(MountIter::new(sample())).unwrap();
+        assert_eq!(mounts.iter().map(|m| m.file.to_str().unwrap()).collect::<Vec<_>>(), vec!("/sys/fs/cgroup"));
+        let boot = MountEntry::from_str("/dev/sda1 /boot ext4 rw 0 0").unwrap();
+        assert!(MountQuery::new().matches(&boot));
+        assert!(MountQuery::new().spec("/dev/sda1").matches(&boot));
+        assert!(!MountQuery::new().spec("/dev/sda2").matches(&boot));
+    }
+
+    #[test]
+    fn test_canonical_order() {
+        let parse = MntOps::parse_list;
+        let mut a = parse("size=4k,relatime,nosuid,mode=755,rw,nodev");
+        let mut b = parse("nodev,mode=755,rw,relatime,size=4k,nosuid");
+        canonical_order(&mut a);
+        canonical_order(&mut b);
+        let line = |ops: &[MntOps]| ops.iter().map(|o| o.to_string()).collect::<Vec<_>>().join(",");
+        assert_eq!(line(&a), "rw,nosuid,nodev,relatime,mode=755,size=4k");
+        assert_eq!(line(&a), line(&b));
+
+        let mut c = parse("noatime,ro,atime");
+        canonical_order(&mut c);
+        assert_eq!(line(&c), "ro,noatime,atime");
+    }
+
+    #[test]
+    fn test_network_fs() {
+        let nfs = MountEntry::from_str("server:/export /mnt/nfs nfs4 rw,relatime,vers=4.2 0 0").unwrap();
+        assert!(nfs.is_network_fs());
+        let sshfs = MountEntry::from_str("user@host:/ /mnt/ssh fuse.sshfs rw,nosuid,nodev 0 0").unwrap();
+        assert!(sshfs.is_network_fs());
+        let network = |file: &str| sample_mount(file).is_network_fs();
+        assert!(!network("/"));
+        assert!(!network("/proc/fs/nfsd"));
+        assert!(!network("/var/lib/nfs/rpc_pipefs"));
+    }
+
+    #[test]
+    fn test_device_node() {
+        let mounts = all_mounts_from(MountIter::new(sample())).unwrap();
+        let luks = Path::new("/dev/mapper/luks-3f6b0024-3d3b-4a8d-a1c5-53e3c8a4ea53");
+        let found: Vec<_> = mounts.iter().filter(|m| m.contains(&MountParam::DeviceNode(luks))).collect();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].file, PathBuf::from("/"));
+        assert!(!mounts.iter().any(|m| m.contains(&MountParam::DeviceNode(Path::new("sysfs")))));
+
+        #[cfg(unix)]
+        {
+            let base = TempDir::new("device-node");
+            fs::create_dir_all(base.join("mapper")).unwrap();
+            File::create(base.join("dm-0")).unwrap();
+            symlink("../dm-0", base.join("mapper").join("luks")).unwrap();
+            let mount = MountEntry::from_str(&format!("{} / xfs rw 0 0", base.join("mapper").join("luks").display())).unwrap();
+            assert!(mount.contains(&MountParam::DeviceNode(&base.join("dm-0"))));
+            assert!(!mount.contains(&MountParam::Spec(base.join("dm-0").to_str().unwrap())));
+        }
+    }
+
+    #[test]
+    fn test_skip_pseudo() {
+        let mounts = MountIter::new(sample()).skip_pseudo().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(mounts.iter().map(|m| m.file.to_str().unwrap()).collect::<Vec<_>>(),
+                   vec!("/", "/boot", "/run/media/user/SDCARD"));
+        let buf = Cursor::new(b"proc /proc proc rw 0 0\n/dev/sda1 /boot\n".as_ref());
+        let mut mounts = MountIter::new(buf).skip_pseudo();
+        assert!(mounts.next().unwrap().is_err());
+        assert!(mounts.next().is_none());
+    }
+
+    #[test]
+    fn test_only_mount_points() {
+        let mut mounts = all_mounts_from(MountIter::new(sample())).unwrap();
+        mounts.push(MountEntry::from_str("/dev/sdb1 /boot ext4 rw 0 0").unwrap());
+        let files = mounts.only_mount_points();
+        assert_eq!(files.len(), mounts.len() - 1);
+        assert_eq!(&files[..3], &[PathBuf::from("/"), PathBuf::from("/boot"), PathBuf::from("/dev")]);
+        assert!(files.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_matches_with() {
+        let mount = MountEntry::from_str("/dev/sdb1 /media/usb vfat rw 0 0").unwrap();
+        let strict = MatchOptions::default();
+        let vfat = MountParam::VfsType("VFAT");
+        assert!(!mount.matches_with(&vfat, &strict));
+        assert!(mount.matches_with(&vfat, &MatchOptions { case_insensitive_vfstype: true, ..strict }));
+
+        let beneath = MountParam::File(Path::new("/media/usb/photos"));
+        assert!(!mount.matches_with(&beneath, &strict));
+        assert!(mount.matches_with(&beneath, &MatchOptions { prefix_mountpoint: true, ..strict }));
+        assert!(!mount.matches_with(&MountParam::File(Path::new("/media")),
+                                    &MatchOptions { prefix_mountpoint: true, ..strict }));
+
+        #[cfg(unix)]
+        {
+            let base = TempDir::new("matches-with");
+            fs::create_dir_all(base.join("real")).unwrap();
+            symlink("real", base.join("link")).unwrap();
+            let mount = MountEntry::from_str(&format!("tmpfs {} tmpfs rw 0 0", base.join("real").display())).unwrap();
+            let link = base.join("link");
+            assert!(!mount.matches_with(&MountParam::File(&link), &strict));
+            assert!(mount.matches_with(&MountParam::File(&link), &MatchOptions { canonicalize_paths: true, ..strict }));
+            assert_eq!(mount.contains(&MountParam::File(&link)), mount.matches_with(&MountParam::File(&link), &strict));
+        }
+    }
+
+    #[test]
+    fn test_selinux_context() {
+        assert!(sample_mount("/sys").has_seclabel());
+        assert!(!sample_mount("/proc").has_seclabel());
+        assert_eq!(sample_mount("/sys").selinux_context(), None);
+
+        let mount = MountEntry::from_str("tmpfs /mnt tmpfs \
+            rw,fscontext=system_u:object_r:tmpfs_t:s0,context=\"system_u:object_r:tmp_t:s0:c1,c2\",nodev 0 0").unwrap();
+        assert_eq!(mount.selinux_context(), Some("system_u:object_r:tmp_t:s0:c1,c2"));
+        assert!(mount.contains(&MountParam::MntOps(&MntOps::Dev(false))));
+        let mount = MountEntry::from_str("tmpfs /mnt tmpfs defcontext=system_u:object_r:tmp_t:s0 0 0").unwrap();
+        assert_eq!(mount.selinux_context(), Some("system_u:object_r:tmp_t:s0"));
+    }
+
+    #[test]
+    fn test_can_remount_ro() {
+        let mounts = all_mounts_from(MountIter::new(sample())).unwrap();
+        assert!(sample_mount("/boot").can_remount_ro(&mounts));
+        assert!(!sample_mount("/").can_remount_ro(&mounts));
+        assert!(!sample_mount("/sys").can_remount_ro(&mounts));
+
+        let mut mounts = mounts;
+        mounts.push(MountEntry::from_str("/dev/sda3 /boot/efi vfat rw 0 0").unwrap());
+        let boot = sample_mount("/boot");
+        assert!(!boot.can_remount_ro(&mounts));
+        mounts.pop();
+        mounts.push(MountEntry::from_str("/dev/sda3 /boot/efi vfat ro 0 0").unwrap());
+        let boot = sample_mount("/boot");
+        assert!(boot.can_remount_ro(&mounts));
+        let ro = MountEntry::from_str("/dev/sdb1 /mnt ext4 ro 0 0").unwrap();
+        assert!(!ro.can_remount_ro(::std::slice::from_ref(&ro)));
+
+        // A mount point hidden by an over-mount stays beneath the mount it was mounted on
+        let buf = Cursor::new(b"/dev/sda1 / ext4 rw 0 0\n/dev/sdb1 /a ext4 rw 0 0\n/dev/sdc1 /a/b ext4 rw 0 0\n\
+                                /dev/sdd1 /a ext4 rw 0 0\n".as_ref());
+        let mounts = all_mounts_from(MountIter::new(buf)).unwrap();
+        assert!(!mounts[1].can_remount_ro(&mounts));
+        assert!(mounts[3].can_remount_ro(&mounts));
+    }
+
+    #[test]
+    fn test_scan_all_namespaces_from() {
+        let base = TempDir::new("scan");
+        for pid in &["1", "42", "self"] {
+            fs::create_dir_all(base.join(pid)).unwrap();
+        }
+        fs::write(base.join("1").join("mounts"), "/dev/sda1 / ext4 rw 0 0\nproc /proc proc rw 0 0\n").unwrap();
+        fs::write(base.join("self").join("mounts"), "/dev/sda1 / ext4 rw 0 0\n").unwrap();
+        // A vanished process has no mounts file
+        fs::create_dir_all(base.join("43")).unwrap();
+        fs::write(base.join("42").join("mounts"), "/dev/sda1 / ext4 rw 0 0\nproc /proc proc rw 0 0\n").unwrap();
+        let namespaces = scan_all_namespaces_from(&base).unwrap();
+        let mut pids = namespaces.keys().cloned().collect::<Vec<_>>();
+        pids.sort();
+        assert_eq!(pids, vec!(1, 42));
+        assert_eq!(namespace_fingerprint(&namespaces[&1]), namespace_fingerprint(&namespaces[&42]));
+
+        fs::write(base.join("42").join("mounts"), "/dev/sda1 relative ext4 rw 0 0\n").unwrap();
+        assert!(scan_all_namespaces_from(&base).unwrap_err().is_parse());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_scan_all_namespaces() {
+        let namespaces = scan_all_namespaces().unwrap();
+        let mounts = &namespaces[&::std::process::id()];
+        assert!(mounts.iter().any(|m| m.file == Path::new("/")));
+    }
+
+    #[test]
+    fn test_security_flags() {
+        let mounts = all_mounts_from(MountIter::new(sample())).unwrap();
+        assert_eq!(sample_mount("/sys").security_flags(), SecurityFlags {
+            nosuid: true,
+            nodev: true,
+            noexec: true,
+            readonly: false,
+        });
+        assert_eq!(sample_mount("/").security_flags(), SecurityFlags::default());
+        let lax = mounts.iter().filter(|m| {
+            let flags = m.security_flags();
+            !flags.readonly && !flags.noexec && !flags.nosuid
+        }).count();
+        assert_eq!(lax, 11);
+        assert!(MntOps::Write(false).is_security_relevant());
+        assert!(MntOps::Suid(true).is_security_relevant());
+        assert!(!MntOps::Atime(false).is_security_relevant());
+        assert!(!MntOps::Extra("seclabel".to_string()).is_security_relevant());
+    }
+
+    #[test]
+    fn test_extra_fields() {
+        let mount = MountEntry::from_str("/dev/sda1 /boot ext4 rw 0 2").unwrap();
+        assert!(mount.extra_fields.is_empty());
+        let line = "/dev/sda1 /boot ext4 rw 0 2 future 42";
+        let mount = MountEntry::from_str(line).unwrap();
+        assert_eq!(mount.extra_fields, vec!("future".to_string(), "42".to_string()));
+        assert_eq!(mount.to_string(), line);
+    }
+
+    #[test]
+    fn test_findmnt_tree() {
+        let mounts = get_submounts_from("/sys", MountIter::new(Cursor::new(b"\
+            sysfs /sys sysfs rw,nosuid 0 0\n\
+            securityfs /sys/kernel/security securityfs ro,nosuid 0 0\n\
+            tmpfs /sys/fs/cgroup tmpfs ro 0 0\n\
+            cgroup /sys/fs/cgroup/systemd cgroup rw 0 0\n\
+            cgroup /sys/fs/cgroup/cpuset cgroup rw 0 0\n\
+            pstore /sys/fs/pstore pstore rw 0 0\n\
+            ".as_ref()))).unwrap();
+        assert_eq!(findmnt_tree(&mounts), "\
+            /sys  sysfs  sysfs  rw,nosuid\n\
+            ├─/sys/kernel/security  securityfs  securityfs  ro,nosuid\n\
+            ├─/sys/fs/cgroup  tmpfs  tmpfs  ro\n\
+            │ ├─/sys/fs/cgroup/systemd  cgroup  cgroup  rw\n\
+            │ └─/sys/fs/cgroup/cpuset  cgroup  cgroup  rw\n\
+            └─/sys/fs/pstore  pstore  pstore  rw\n");
+    }
+
+    #[test]
+    fn test_mntops_parse_list() {
+        assert_eq!(MntOps::parse_list("rw,seclabel,nosuid,size=7898068k,nr_inodes=1974517,mode=755"), vec!(
+            MntOps::Write(true),
+            MntOps::Extra("seclabel".to_string()),
+            MntOps::Suid(false),
+            MntOps::Extra("size=7898068k".to_string()),
+            MntOps::Extra("nr_inodes=1974517".to_string()),
+            MntOps::Extra("mode=755".to_string()),
+        ));
+        assert_eq!(MntOps::parse_list(","), vec!());
+        assert_eq!(MntOps::parse_list("context=\"a,b\",ro"),
+                   vec!(MntOps::Extra("context=\"a,b\"".to_string()), MntOps::Write(false)));
+    }
+
+    #[test]
+    fn test_canonical_sort() {
+        let mut a = all_mounts_from(MountIter::new(sample())).unwrap();
+        a.push(MountEntry::from_str("tmpfs /tmp tmpfs ro 0 0").unwrap());
+        let mut b = a.clone();
+        b.reverse();
+        b.swap(0, 7);
+        canonical_sort(&mut a);
+        canonical_sort(&mut b);
+        assert_eq!(a, b);
+        assert_eq!(a[0].file, PathBuf::from("/"));
+        let tmp: Vec<_> = a.iter().filter(|m| m.file == Path::new("/tmp")).map(|m| m.mount_options_string()).collect();
+        assert_eq!(tmp, vec!("ro", "rw,seclabel"));
+    }
+
+    #[test]
+    fn test_real_root_mount() {
+        let mounts = b"rootfs / rootfs rw 0 0\n/dev/sda1 / xfs rw 0 0\nproc /proc proc rw 0 0\n";
+        let root = get_real_root_mount_from(MountIter::new(Cursor::new(mounts.as_ref()))).unwrap().unwrap();
+        assert_eq!(root.vfstype, "xfs");
+        assert!(!root.is_initramfs_rootfs());
+
+        let early = b"rootfs / rootfs rw 0 0\nproc /proc proc rw 0 0\n";
+        let root = get_root_mount_from(MountIter::new(Cursor::new(early.as_ref()))).unwrap().unwrap();
+        assert!(root.is_initramfs_rootfs());
+        assert_eq!(get_real_root_mount_from(MountIter::new(Cursor::new(early.as_ref()))).unwrap(), None);
+    }
+
+    #[test]
+    fn test_mntops_family() {
+        let atime = MountEntry::from_str("/dev/sda1 /a ext4 rw,atime 0 0").unwrap();
+        let noatime = MountEntry::from_str("/dev/sda2 /b ext4 ro,noatime 0 0").unwrap();
+        let unset = MountEntry::from_str("/dev/sda3 /c ext4 rw,relatime 0 0").unwrap();
+        let family = MountParam::MntOpsFamily(OpFamily::Atime);
+        assert!(atime.contains(&family));
+        assert!(noatime.contains(&family));
+        assert!(!unset.contains(&family));
+        assert!(unset.contains(&MountParam::MntOpsFamily(OpFamily::RelAtime)));
+        assert_eq!(MntOps::Write(false).family(), Some(OpFamily::Write));
+        assert_eq!(MntOps::Propagation(Propagation::Slave, true).family(), Some(OpFamily::Propagation));
+        assert_eq!(MntOps::Extra("seclabel".to_string()).family(), None);
+    }
+
+    #[test]
+    fn test_try_collect() {
+        assert_eq!(MountIter::new(sample()).try_collect().unwrap().len(), 25);
+        let buf = b"proc /proc proc rw 0 0\n/dev/sda1 /boot ext4\n/dev/sda2 relative ext4 rw 0 0\n";
+        let err = MountIter::new(Cursor::new(buf.as_ref())).try_collect().unwrap_err();
+        assert!(err.is_parse());
+        // Lines are numbered from 0
+        assert_eq!(err.to_string(), "Mount parsing: Failed at line 1: Missing field #4 (mntops)");
+        let err = MountIter::new(Cursor::new(b"/dev/sda1".as_ref())).next().unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "Mount parsing: Failed at line 0: Missing field #2 (file)");
+    }
+
+    #[test]
+    fn test_mount_table() {
+        let table = MountIter::new(sample()).collect::<Result<MountTable, _>>().unwrap();
+        assert_eq!(table.entries().len(), 25);
+        assert_eq!(table.get_mount("/boot/grub2").map(|m| m.spec.as_str()), Some("/dev/sda1"));
+        assert_eq!(table.get_mount("/home/user").map(|m| m.vfstype.as_str()), Some("xfs"));
+        let submounts = table.get_submounts("/run/user");
+        assert_eq!(submounts.iter().map(|m| m.file.to_str().unwrap()).collect::<Vec<_>>(),
+                   vec!("/run/user/1000", "/run/user/1000/gvfs"));
+        let tree = table.tree();
+        assert_eq!(tree.len(), 25);
+        assert_eq!((tree[0].0.file.to_str().unwrap(), tree[0].1), ("/", 0));
+        assert!(tree.iter().any(|&(m, depth)| m.file == Path::new("/run/user/1000/gvfs") && depth == 3));
+        assert_eq!(table.clone().into_vec(), all_mounts_from(MountIter::new(sample())).unwrap());
+        assert!(MountTable::new_from_proc().unwrap().get_mount("/").is_some());
+    }
+
+    #[test]
+    fn test_fill_defaults() {
+        let mut ops = MntOps::parse_list("rw");
+        fill_defaults(&mut ops);
+        assert_eq!(ops, MntOps::parse_list("rw,suid,dev,exec,atime,diratime"));
+
+        let mut ops = MntOps::parse_list("ro,nosuid,relatime,seclabel");
+        fill_defaults(&mut ops);
+        assert_eq!(ops, MntOps::parse_list("ro,nosuid,relatime,seclabel,dev,exec,diratime"));
+        let mut mount = MountEntry::from_str("/dev/sda1 / ext4 ro,noatime 0 0").unwrap();
+        let flags = mount.flags();
+        fill_defaults(&mut mount.mntops);
+        assert_eq!(mount.flags(), flags);
+    }
+
+    #[test]
+    fn test_sort_tree_deep_overmounts() {
+        let tmp = MountEntry::from_str("tmpfs /a tmpfs rw 0 0").unwrap();
+        let mut mounts = vec!(MountEntry::from_str("/dev/sda1 / ext4 rw 0 0").unwrap());
+        mounts.extend(vec!(tmp; 100_000));
+        mounts.push(MountEntry::from_str("proc /a/proc proc rw 0 0").unwrap());
+        let sorted = sort_tree(mounts);
+        assert_eq!(sorted.len(), 100_002);
+        assert_eq!(sorted[0].file, PathBuf::from("/"));
+        assert_eq!(sorted[100_001].file, PathBuf::from("/a/proc"));
+        let tree = findmnt_tree(&sorted[..3]);
+        assert_eq!(tree.lines().count(), 3);
+    }
 }
@@ -0,0 +1,29 @@
+// Copyright (C) 2014-2015 Mickaël Salaün
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Common items to import at once
+//!
+//! ```
+//! use mnt::prelude::*;
+//!
+//! let mounts: Vec<MountEntry> = get_submounts("/").unwrap();
+//! let _ = mounts.remove_overlaps::<&str>(&vec!());
+//! let _: Option<MountEntry> = get_mount("/").unwrap();
+//! let _: MountIter<_> = MountIter::new(&b"rootfs / rootfs rw 0 0\n"[..]);
+//! let _ = MountParam::MntOps(&MntOps::Write(true));
+//! ```
+
+pub use parse::{MntOps, MountEntry, MountIter, MountParam, VecMountEntry};
+pub use parse::{get_mount, get_mount_from, get_mount_writable};
+pub use parse::{get_submounts, get_submounts_from, get_submounts_many, get_submounts_many_from};
@@ -0,0 +1,150 @@
+// Copyright (C) 2014-2015 Mickaël Salaün
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use parse::{path_from_bytes, unescape_octal, MountEntry};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str;
+
+const DEV_DISK: &str = "/dev/disk";
+
+/// Encode a tag value the way udev names the `by-*` symlinks (e.g. `\x20` for a space)
+fn encode_udev_name(value: &[u8]) -> Vec<u8> {
+    // Valid UTF-8 is kept as is
+    let utf8 = str::from_utf8(value).is_ok();
+    let mut ret = Vec::with_capacity(value.len());
+    for &c in value {
+        if c.is_ascii_alphanumeric() || b"#+-.:=@_".contains(&c) || (utf8 && c >= 0x80) {
+            ret.push(c);
+        } else {
+            ret.extend_from_slice(format!("\\x{:02x}", c).as_bytes());
+        }
+    }
+    ret
+}
+
+/// Resolve a spec to the real device path using a custom */dev/disk* directory
+///
+/// `UUID=`, `LABEL=`, `PARTUUID=` and `PARTLABEL=` specs are looked up in the matching `by-*`
+/// directory of `dev_disk`, once their fstab escapes (e.g. `\040` for a space) are decoded.  Any
+/// other spec must be an absolute path.  Symlinks are then canonicalized.
+pub fn resolve_spec_from<T>(spec: &str, dev_disk: T) -> io::Result<PathBuf> where T: AsRef<Path> {
+    let by_tag = [
+        ("UUID=", "by-uuid"),
+        ("LABEL=", "by-label"),
+        ("PARTUUID=", "by-partuuid"),
+        ("PARTLABEL=", "by-partlabel"),
+    ];
+    let invalid = |detail: &str| io::Error::new(io::ErrorKind::InvalidInput, format!("{}: {}", detail, spec));
+    let path = match by_tag.iter().find(|&&(tag, _)| spec.starts_with(tag)) {
+        Some(&(tag, dir)) => {
+            let value = unescape_octal(&spec.as_bytes()[tag.len()..]);
+            // Don't get out of the by-* directory
+            if value.is_empty() || value.contains(&b'/') || value == b"." || value == b".." {
+                return Err(invalid("Invalid tag value"));
+            }
+            dev_disk.as_ref().join(dir).join(path_from_bytes(&encode_udev_name(&value)))
+        }
+        // Pseudo sources (e.g. proc) must not be resolved against the current directory
+        None if spec.starts_with('/') => PathBuf::from(spec),
+        None => return Err(invalid("Not an absolute path")),
+    };
+    fs::canonicalize(path)
+}
+
+/// Resolve a spec to the real device path using */dev/disk*
+pub fn resolve_spec(spec: &str) -> io::Result<PathBuf> {
+    resolve_spec_from(spec, DEV_DISK)
+}
+
+impl MountEntry {
+    /// Check if a fstab spec designates the source of this mount point using a custom */dev/disk*
+    ///
+    /// Both specs are resolved with `resolve_spec_from()`.  If one of them can't be resolved,
+    /// fallback to a string comparison.
+    pub fn matches_source_from<T>(&self, fstab_spec: &str, dev_disk: T) -> bool where T: AsRef<Path> {
+        match (resolve_spec_from(fstab_spec, &dev_disk), resolve_spec_from(&self.spec, &dev_disk)) {
+            (Ok(wanted), Ok(current)) => wanted == current,
+            _ => fstab_spec == self.spec,
+        }
+    }
+
+    /// Check if a fstab spec designates the source of this mount point using */dev/disk*
+    pub fn matches_source(&self, fstab_spec: &str) -> bool {
+        self.matches_source_from(fstab_spec, DEV_DISK)
+    }
+}
+
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::fs::{self, File};
+    use std::io;
+    use std::os::unix::fs::symlink;
+    use std::str::FromStr;
+    use parse::MountEntry;
+    use parse::test::TempDir;
+    use super::resolve_spec_from;
+
+    fn fake_dev_disk(name: &str) -> TempDir {
+        let base = TempDir::new(name);
+        fs::create_dir_all(base.join("disk").join("by-uuid")).unwrap();
+        fs::create_dir_all(base.join("disk").join("by-label")).unwrap();
+        File::create(base.join("sda1")).unwrap();
+        symlink("../../sda1", base.join("disk").join("by-uuid").join("0a1b-2c3d")).unwrap();
+        symlink("../../sda1", base.join("disk").join("by-label").join("boot")).unwrap();
+        symlink("../../sda1", base.join("disk").join("by-label").join("My\\x20Disk")).unwrap();
+        symlink("../../sda1", base.join("disk").join("by-label").join("caf\u{e9}")).unwrap();
+        symlink("sda1", base.join("boot-link")).unwrap();
+        base
+    }
+
+    #[test]
+    fn test_resolve_spec() {
+        let base = fake_dev_disk("resolve-spec");
+        let dev_disk = base.join("disk");
+        let sda1 = fs::canonicalize(base.join("sda1")).unwrap();
+        assert_eq!(resolve_spec_from("UUID=0a1b-2c3d", &dev_disk).ok(), Some(sda1.clone()));
+        assert_eq!(resolve_spec_from("LABEL=boot", &dev_disk).ok(), Some(sda1.clone()));
+        let link = base.join("boot-link");
+        assert_eq!(resolve_spec_from(link.to_str().unwrap(), &dev_disk).ok(), Some(sda1.clone()));
+        assert!(resolve_spec_from("UUID=ffff-ffff", &dev_disk).is_err());
+        assert_eq!(resolve_spec_from("LABEL=My\\040Disk", &dev_disk).ok(), Some(sda1.clone()));
+        assert_eq!(resolve_spec_from("LABEL=caf\u{e9}", &dev_disk).ok(), Some(sda1.clone()));
+        for spec in &["LABEL=../../sda1", "LABEL=..", "LABEL=", "UUID=a\\057b"] {
+            assert_eq!(resolve_spec_from(spec, &dev_disk).map_err(|e| e.kind()), Err(io::ErrorKind::InvalidInput));
+        }
+        // Not resolved against the current directory
+        assert_eq!(resolve_spec_from("proc", &dev_disk).map_err(|e| e.kind()), Err(io::ErrorKind::InvalidInput));
+    }
+
+    #[test]
+    fn test_matches_source() {
+        let base = fake_dev_disk("matches-source");
+        let dev_disk = base.join("disk");
+        let line = format!("{} /boot ext4 rw 0 0", base.join("sda1").display());
+        let boot = MountEntry::from_str(&line).unwrap();
+        assert!(boot.matches_source_from("UUID=0a1b-2c3d", &dev_disk));
+        assert!(boot.matches_source_from("LABEL=boot", &dev_disk));
+        assert!(boot.matches_source_from(base.join("boot-link").to_str().unwrap(), &dev_disk));
+        assert!(!boot.matches_source_from("UUID=ffff-ffff", &dev_disk));
+        assert!(!boot.matches_source_from("tmpfs", &dev_disk));
+
+        let tmp = MountEntry::from_str("tmpfs /tmp tmpfs rw 0 0").unwrap();
+        assert!(tmp.matches_source_from("tmpfs", &dev_disk));
+        let usb = MountEntry::from_str(&format!("{} /media ext4 rw 0 0", base.join("sda1").display())).unwrap();
+        assert!(usb.matches_source_from("LABEL=My\\040Disk", &dev_disk));
+    }
+}
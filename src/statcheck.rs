@@ -0,0 +1,53 @@
+// Copyright (C) 2014-2015 Mickaël Salaün
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use parse::MountEntry;
+use std::fs;
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+
+impl MountEntry {
+    /// Check if the source is a block device thanks to `stat(2)`
+    ///
+    /// A regular file (e.g. a loop source) is not, and neither is a pseudo source which is not a
+    /// path (cf. `source_device_path`).
+    pub fn source_is_block_device(&self) -> io::Result<bool> {
+        match self.source_device_path() {
+            Some(path) => Ok(fs::metadata(path)?.file_type().is_block_device()),
+            None => Ok(false),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use parse::MountEntry;
+    use std::path::Path;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_source_is_block_device() {
+        let mount = MountEntry::from_str("/dev/null /mnt ext4 rw 0 0").unwrap();
+        assert!(!mount.source_is_block_device().unwrap());
+        let mount = MountEntry::from_str("tmpfs /tmp tmpfs rw 0 0").unwrap();
+        assert!(!mount.source_is_block_device().unwrap());
+        let mount = MountEntry::from_str("/nonexistent/sda1 /mnt ext4 rw 0 0").unwrap();
+        assert!(mount.source_is_block_device().is_err());
+        if Path::new("/dev/loop0").exists() {
+            let mount = MountEntry::from_str("/dev/loop0 /mnt ext4 rw 0 0").unwrap();
+            assert!(mount.source_is_block_device().unwrap());
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// Copyright (C) 2014-2015 Mickaël Salaün
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Lesser General Public License as published by
+// the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Lesser General Public License for more details.
+//
+// You should have received a copy of the GNU Lesser General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+extern crate libc;
+
+use parse::MountEntry;
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+
+/// Filesystem types without any block accounting, for which `statvfs(2)` only returns zeros
+///
+/// Memory-backed filesystems (e.g. tmpfs) have a size limit and are not part of it.
+const NO_USAGE_FS_TYPES: &[&str] = &[
+    "autofs", "binfmt_misc", "bpf", "cgroup", "cgroup2", "configfs", "debugfs", "devpts",
+    "efivarfs", "fusectl", "mqueue", "nfsd", "nsfs", "proc", "pstore", "rpc_pipefs", "securityfs",
+    "selinuxfs", "sysfs", "tracefs",
+];
+
+/// Space usage of a mounted filesystem, in bytes
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MountUsage {
+    pub total: u64,
+    pub free: u64,
+    /// Free space available to unprivileged users
+    pub available: u64,
+}
+
+impl MountEntry {
+    /// Get the space usage of the mounted filesystem thanks to `statvfs(2)`
+    ///
+    /// Filesystems without block accounting (e.g. sysfs or proc) have no meaningful usage and
+    /// return an `InvalidInput` error.
+    pub fn usage(&self) -> io::Result<MountUsage> {
+        if NO_USAGE_FS_TYPES.contains(&self.vfstype.as_str()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      format!("No space usage for a {} filesystem", self.vfstype)));
+        }
+        let path = CString::new(self.file.as_os_str().as_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let mut stat: libc::statvfs = unsafe { mem::zeroed() };
+        if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let frsize = stat.f_frsize as u64;
+        Ok(MountUsage {
+            total: stat.f_blocks as u64 * frsize,
+            free: stat.f_bfree as u64 * frsize,
+            available: stat.f_bavail as u64 * frsize,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use parse::{get_mount, MountEntry};
+    use std::io;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_usage_root() {
+        let root = get_mount("/").unwrap().unwrap();
+        let usage = root.usage().unwrap();
+        assert!(usage.total > 0);
+        assert!(usage.free <= usage.total);
+        assert!(usage.available <= usage.free);
+    }
+
+    #[test]
+    fn test_usage_pseudo_fs() {
+        let proc_mount = MountEntry::from_str("proc /proc proc rw 0 0").unwrap();
+        assert_eq!(proc_mount.usage().map_err(|e| e.kind()), Err(io::ErrorKind::InvalidInput));
+    }
+
+    #[test]
+    fn test_usage_memory_and_overlay() {
+        // A container root filesystem or a tmpfs are pseudo filesystems with a real usage
+        for line in &["overlay / overlay rw 0 0", "tmpfs / tmpfs rw 0 0"] {
+            let mount = MountEntry::from_str(line).unwrap();
+            assert!(mount.is_pseudo_fs());
+            assert!(mount.usage().unwrap().total > 0);
+        }
+    }
+}